@@ -0,0 +1,311 @@
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+use crossbeam_utils::Backoff;
+use crate::error::{Error, TrySendError};
+use crate::queue::{ringbuffer, ringbuffer_overwrite, RingBufferReceiver, RingBufferSender};
+
+/// 队列已满时写入者的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// `try_send` 和 `send` 都通过 `TrySendError::Full` 将值原样返回
+    Reject,
+    /// 按等待策略等待读取者腾出空位
+    #[default]
+    Block,
+    /// 丢弃新写入的值
+    DropNewest,
+    /// 覆盖最早的数据，读取者通过 `Receiver::missed` 得知错过了多少数据
+    DropOldest,
+}
+
+/// 写入者等待空位、读取者等待数据时的等待方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaitStrategy {
+    /// 一直自旋，延迟最低，但会占满一个核
+    Spin,
+    /// 先自旋一段时间，之后每次让出时间片
+    SpinThenYield,
+    /// 短暂自旋之后挂起线程，由对方唤醒
+    #[default]
+    Park,
+    /// 使用 crossbeam 的 `Backoff`，自旋次数指数增长，之后让出时间片
+    Backoff,
+}
+
+/// SpinThenYield 在让出时间片之前的自旋次数
+const SPIN_LIMIT: u32 = 64;
+
+impl WaitStrategy {
+    /// 不挂起线程的等待方式，反复调用 f 直到返回 Some 或者超过 deadline
+    fn spin_until<R>(self, deadline: Option<Instant>, mut f: impl FnMut() -> Option<R>) -> Option<R> {
+        let backoff = Backoff::new();
+        let mut spins = 0;
+        loop {
+            if let Some(res) = f() {
+                return Some(res);
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return None;
+            }
+            match self {
+                WaitStrategy::Spin => std::hint::spin_loop(),
+                WaitStrategy::SpinThenYield if spins < SPIN_LIMIT => {
+                    spins += 1;
+                    std::hint::spin_loop();
+                }
+                WaitStrategy::SpinThenYield => std::thread::yield_now(),
+                WaitStrategy::Park | WaitStrategy::Backoff => backoff.snooze(),
+            }
+        }
+    }
+}
+
+/// 通道构造器，选择缓冲区大小、溢出策略和等待策略
+/// ```
+/// use lockfree::channel::{Builder, Overflow, WaitStrategy};
+/// let (mut tx, mut rx) = Builder::<u32>::new()
+///     .capacity::<16>()
+///     .overflow(Overflow::DropOldest)
+///     .wait_strategy(WaitStrategy::SpinThenYield)
+///     .build();
+/// tx.send(1).unwrap();
+/// assert_eq!(rx.recv().unwrap(), 1);
+/// ```
+pub struct Builder<T, const SIZE: usize = 4> {
+    overflow: Overflow,
+    wait: WaitStrategy,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Builder<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Builder {
+            overflow: Overflow::default(),
+            wait: WaitStrategy::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+// 只保存配置，不持有 T，手动实现以免 derive 要求 T: Clone/Copy/Debug
+impl<T, const SIZE: usize> std::fmt::Debug for Builder<T, SIZE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Builder")
+            .field("capacity", &SIZE)
+            .field("overflow", &self.overflow)
+            .field("wait", &self.wait)
+            .finish()
+    }
+}
+
+impl<T, const SIZE: usize> Clone for Builder<T, SIZE> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const SIZE: usize> Copy for Builder<T, SIZE> {}
+
+impl<T> Default for Builder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const SIZE: usize> Builder<T, SIZE> {
+    /// 设置缓冲区大小，N 必须是2的幂，否则编译失败，和 `ringbuffer::<T, N>` 一样可以容纳 N 个元素
+    #[inline]
+    pub fn capacity<const N: usize>(self) -> Builder<T, N> {
+        Builder {
+            overflow: self.overflow,
+            wait: self.wait,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 设置队列已满时的处理方式，默认为 `Overflow::Block`
+    #[inline]
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// 设置等待方式，默认为 `WaitStrategy::Park`
+    #[inline]
+    pub fn wait_strategy(mut self, wait: WaitStrategy) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// 创建 单生产者-单消费者 通道
+    pub fn build(self) -> (Sender<T, SIZE>, Receiver<T, SIZE>) {
+        let (tx, rx) = match self.overflow {
+            Overflow::DropOldest => ringbuffer_overwrite(),
+            _ => ringbuffer(),
+        };
+        let sender = Sender {
+            inner: tx,
+            overflow: self.overflow,
+            wait: self.wait,
+        };
+        let receiver = Receiver {
+            inner: rx,
+            wait: self.wait,
+        };
+        (sender, receiver)
+    }
+}
+
+/// 由 `Builder` 创建的写入者，写入行为由溢出策略和等待策略决定
+pub struct Sender<T, const SIZE: usize> {
+    inner: RingBufferSender<T, SIZE>,
+    overflow: Overflow,
+    wait: WaitStrategy,
+}
+
+impl<T, const SIZE: usize> Sender<T, SIZE> {
+    #[inline]
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    #[inline]
+    pub fn wait_strategy(&self) -> WaitStrategy {
+        self.wait
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// 读取者是否已经被释放
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.is_disconnected()
+    }
+
+    /// 写入数据，不会等待
+    /// 队列已满时，`Reject` 和 `Block` 将值原样返回，`DropNewest` 丢弃新值并返回 Ok，`DropOldest` 覆盖最早的数据
+    pub fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        match self.inner.try_push(value) {
+            Err(TrySendError::Full(_)) if self.overflow == Overflow::DropNewest => Ok(()),
+            res => res,
+        }
+    }
+
+    /// 写入数据，只有 `Block` 会在队列已满时按等待策略等待，其它策略同 `try_send`
+    /// 返回 `TrySendError` 是因为 `Reject` 在队列已满时也要通过 `TrySendError::Full` 将值原样返回，
+    /// `Block` 一直等到有空位，`DropNewest` 和 `DropOldest` 写满时返回 Ok，这三种策略只会返回 `Disconnected` 或 `Closed`
+    pub fn send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        if self.overflow != Overflow::Block {
+            return self.try_send(value);
+        }
+        if self.wait == WaitStrategy::Park {
+            return self.inner.send(value).map_err(TrySendError::from);
+        }
+        let inner = &mut self.inner;
+        let mut value = Some(value);
+        self.wait
+            .spin_until(None, || match inner.try_push(value.take()?) {
+                Err(TrySendError::Full(v)) => {
+                    value = Some(v);
+                    None
+                }
+                res => Some(res),
+            })
+            .expect("waiting without deadline never times out")
+    }
+}
+
+/// 由 `Builder` 创建的读取者，没有数据时按等待策略等待
+pub struct Receiver<T, const SIZE: usize> {
+    inner: RingBufferReceiver<T, SIZE>,
+    wait: WaitStrategy,
+}
+
+impl<T, const SIZE: usize> Receiver<T, SIZE> {
+    #[inline]
+    pub fn wait_strategy(&self) -> WaitStrategy {
+        self.wait
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// 写入者是否已经被释放
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.is_disconnected()
+    }
+
+    /// 最近一次取出的数据之前被覆盖的元素个数，只有 `Overflow::DropOldest` 会覆盖数据
+    #[inline]
+    pub fn missed(&self) -> usize {
+        self.inner.missed()
+    }
+
+    /// 取出数据，不会等待
+    #[inline]
+    pub fn try_recv(&mut self) -> Result<T, Error> {
+        self.inner.try_pop()
+    }
+
+    /// 取出数据，没有数据时按等待策略等待
+    #[inline]
+    pub fn recv(&mut self) -> Result<T, Error> {
+        self.recv_until(None)
+    }
+
+    /// 取出数据，没有数据时最多等待 timeout，超时返回 `Error::Timeout`
+    #[inline]
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, Error> {
+        self.recv_until(Instant::now().checked_add(timeout))
+    }
+
+    fn recv_until(&mut self, deadline: Option<Instant>) -> Result<T, Error> {
+        if self.wait == WaitStrategy::Park {
+            return match deadline {
+                Some(deadline) => self.inner.recv_deadline(deadline),
+                None => self.inner.recv(),
+            };
+        }
+        let inner = &mut self.inner;
+        self.wait
+            .spin_until(deadline, || match inner.try_pop() {
+                Err(Error::Empty) => None,
+                res => Some(res),
+            })
+            .unwrap_or(Err(Error::Timeout))
+    }
+}
@@ -1,5 +1,3 @@
-#[derive(Debug)]
-pub enum Error {
-    Empty,
-    Full,
-}
\ No newline at end of file
+//! 已经合并为 `crate::error`
+
+pub use crate::error::{Error, SendError, SendTimeoutError, TrySendError};
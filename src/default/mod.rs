@@ -1,3 +1,6 @@
+//! 旧的 `T: Default` 存储的环形缓冲区和 LockFreeValue 路径，已经合并到 `crate::queue` 和 `crate::value`，
+//! 对应 `storage::Defaulted` 存储策略，这里只保留别名方便逐步迁移
+
 pub mod queue;
 pub mod value;
-pub mod error;
\ No newline at end of file
+pub mod error;
@@ -1,163 +1,35 @@
-#![allow(dead_code)]
+#![allow(deprecated)]
 
-use std::fmt::Formatter;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use crossbeam_utils::CachePadded;
-use super::error::Error;
+use crate::storage::Defaulted;
 
-/// 这里其实不需要限制RingBuffer，因为RingBuffer的实现都是符合借用规则的
-/// 所以不必担心安全问题，默认情况下只会有一个线程持有对象，因为没有提供Clone方法，即便用Arc指针
-/// 也无法通过不可变引用修改内部数据
-/// 如果想要修改内部数据就必须在包一层Mutex，这也是完全符合安全原则的
-/// 因此如果想要使用就必须使用unsafe，此时安全由使用者确保
-/// 所以在下面的读写分离实现中，使用了Arc实现内部可变。
-#[derive(Debug)]
-pub struct RingBuffer<T, const SIZE: usize = 4> {
-    m_data: [T; SIZE],
-    idx_head: CachePadded<AtomicUsize>,
-    idx_tail: CachePadded<AtomicUsize>,
-}
-
-impl<T: Default, const SIZE: usize> RingBuffer<T, SIZE> {
-    #[inline]
-    fn new() -> Self {
-        RingBuffer::<T, SIZE> {
-            idx_head: CachePadded::new(AtomicUsize::new(0)),
-            idx_tail: CachePadded::new(AtomicUsize::new(0)),
-            m_data: [(); SIZE].map(|_| Default::default()),
-        }
-    }
-}
-
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self)
-    }
-}
-
-impl std::error::Error for Error {}
+pub use super::error::{Error, SendError, SendTimeoutError, TrySendError};
 
+#[deprecated(note = "use `lockfree::queue::RingBuffer<T, SIZE, Defaulted>`")]
+pub type RingBuffer<T, const SIZE: usize = 4> = crate::queue::RingBuffer<T, SIZE, Defaulted>;
 
-impl<T: Default, const SIZE: usize> RingBuffer<T, SIZE> {
-    #[inline]
-    fn next_idx(cur: usize) -> usize {
-        (cur + 1) & (SIZE - 1)
-    }
+#[deprecated(note = "use `lockfree::queue::RingBufferSender<T, SIZE, Defaulted>`")]
+pub type RingBufferSender<T, const SIZE: usize> = crate::queue::RingBufferSender<T, SIZE, Defaulted>;
 
-    #[inline]
-    fn ring_idx(cur: usize) -> usize {
-        cur & (SIZE - 1)
-    }
+#[deprecated(note = "use `lockfree::queue::RingBufferReceiver<T, SIZE, Defaulted>`")]
+pub type RingBufferReceiver<T, const SIZE: usize> = crate::queue::RingBufferReceiver<T, SIZE, Defaulted>;
 
-    pub fn push(&mut self, value: T) -> Result<(), Error> {
-        let head = self.idx_head.load(Ordering::Acquire);
-        let tail = self.idx_tail.load(Ordering::Acquire);
-        let mut next_head = Self::next_idx(head);
-        if next_head == tail {
-            return Err(Error::Full);
-        }
-        self.m_data[head] = value;
-        self.idx_head.store(next_head, Ordering::Release);
-        Ok(())
-    }
+#[deprecated(note = "use `lockfree::queue::WriteGuard<'a, T, SIZE, Defaulted>`")]
+pub type WriteGuard<'a, T, const SIZE: usize> = crate::queue::WriteGuard<'a, T, SIZE, Defaulted>;
 
-    pub fn pop(&mut self) -> Result<T, Error> {
-        let mut tail = self.idx_tail.load(Ordering::Acquire);
-        let head = self.idx_head.load(Ordering::Acquire);
-        if head == tail {
-            return Err(Error::Empty);
-        }
-        let res = std::mem::replace(&mut self.m_data[tail], T::default());
-        self.idx_tail.store(Self::next_idx(tail), Ordering::Release);
-        Ok(res)
-    }
+#[deprecated(note = "use `lockfree::queue::ReadGuard<'a, T, SIZE, Defaulted>`")]
+pub type ReadGuard<'a, T, const SIZE: usize> = crate::queue::ReadGuard<'a, T, SIZE, Defaulted>;
 
-    #[inline]
-    pub fn is_full(&self) -> bool {
-        let idx_tail = self.idx_tail.load(Ordering::Acquire);
-        let idx_head = self.idx_head.load(Ordering::Acquire);
-        idx_tail == Self::next_idx(idx_head)
-    }
+#[cfg(not(loom))]
+#[deprecated(note = "use `lockfree::queue::WriteChunk<'a, T, SIZE, Defaulted>`")]
+pub type WriteChunk<'a, T, const SIZE: usize> = crate::queue::WriteChunk<'a, T, SIZE, Defaulted>;
 
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        let idx_tail = self.idx_tail.load(Ordering::Acquire);
-        let idx_head = self.idx_head.load(Ordering::Acquire);
-        idx_head == idx_tail
-    }
-
-    #[inline]
-    pub fn size(&self) -> usize {
-        SIZE
-    }
-}
-
-/// 这里采用Reader 和Writer的分离实现
-/// 由于Reader没有实现Clone，所以Reader不能共享所有权
-/// 由于Writer没有实现Clone，所以Writer不能共享所有权
-/// 因此，就实现了 单生产者-单消费者 模式
-
-pub struct RingBufferSender<T, const SIZE: usize> {
-    inner: Arc<RingBuffer<T, SIZE>>,
-}
-
-impl<T: Default, const SIZE: usize> RingBufferSender<T, SIZE> {
-    #[inline]
-    fn is_full(&self) -> bool {
-        self.inner.is_full()
-    }
-
-    #[inline]
-    fn is_empty(&self) -> bool {
-        self.inner.is_empty()
-    }
-
-    #[inline]
-    pub fn size(&self) -> usize {
-        self.inner.size()
-    }
-    fn push(&mut self, value: T) -> Result<(), Error> {
-        unsafe {
-            Arc::get_mut_unchecked(&mut self.inner).push(value)
-        }
-    }
-}
-
-pub struct RingBufferReceiver<T, const SIZE: usize> {
-    inner: Arc<RingBuffer<T, SIZE>>,
-}
-
-impl<T: Default, const SIZE: usize> RingBufferReceiver<T, SIZE> {
-    #[inline]
-    fn is_full(&self) -> bool {
-        self.inner.is_full()
-    }
-
-    #[inline]
-    fn is_empty(&self) -> bool {
-        self.inner.is_empty()
-    }
-
-    #[inline]
-    pub fn size(&self) -> usize {
-        self.inner.size()
-    }
-    fn pop(&mut self) -> Result<T, Error> {
-        unsafe {
-            Arc::get_mut_unchecked(&mut self.inner).pop()
-        }
-    }
-}
+#[cfg(not(loom))]
+#[deprecated(note = "use `lockfree::queue::ReadChunk<'a, T, SIZE, Defaulted>`")]
+pub type ReadChunk<'a, T, const SIZE: usize> = crate::queue::ReadChunk<'a, T, SIZE, Defaulted>;
 
+#[deprecated(note = "use `lockfree::queue::ringbuffer_with::<T, SIZE, Defaulted>`")]
+#[inline]
 pub fn ringbuffer<T: Default, const SIZE: usize>() -> (RingBufferSender<T, SIZE>, RingBufferReceiver<T, SIZE>)
 {
-    let ring = Arc::new(RingBuffer::new());
-    let sender = RingBufferSender {
-        inner: ring.clone(),
-    };
-    let receiver = RingBufferReceiver {
-        inner: ring,
-    };
-    (sender, receiver)
+    crate::queue::ringbuffer_with()
 }
@@ -0,0 +1,321 @@
+//! 所有队列和 LockFreeValue 共用的错误类型
+//!
+//! - `Error`：不携带数据的错误种类，读取、等待和预留槽位失败时返回
+//! - `TrySendError`、`SendTimeoutError`、`SendError`：写入失败时返回，携带未能写入的值，调用方可以取回重试
+//!
+//! 写入错误都可以通过 `From` 或者 `kind` 转换为 `Error`，`Error::with_value` 则带上未能写入的值转换回写入错误
+
+use std::fmt::Formatter;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Error {
+    /// 队列为空，或者没有新值
+    Empty,
+    /// 队列已满
+    Full,
+    /// 等待超时
+    Timeout,
+    /// 另一端已经被释放
+    Disconnected,
+    /// 当前这一端已经关闭，不能再写入
+    Closed,
+}
+
+impl Error {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Error::Empty)
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        matches!(self, Error::Full)
+    }
+
+    #[inline]
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Error::Timeout)
+    }
+
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, Error::Disconnected)
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Error::Closed)
+    }
+
+    /// 带上未能写入的值转换为 `TrySendError`，Empty 和 Timeout 不是写入错误，连同值一起原样返回
+    #[inline]
+    pub fn with_value<T>(self, value: T) -> Result<TrySendError<T>, (Error, T)> {
+        match self {
+            Error::Full => Ok(TrySendError::Full(value)),
+            Error::Disconnected => Ok(TrySendError::Disconnected(value)),
+            Error::Closed => Ok(TrySendError::Closed(value)),
+            Error::Empty | Error::Timeout => Err((self, value)),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Empty => write!(f, "ring buffer is empty"),
+            Error::Full => write!(f, "ring buffer is full"),
+            Error::Timeout => write!(f, "timed out waiting on ring buffer"),
+            Error::Disconnected => write!(f, "ring buffer is disconnected"),
+            Error::Closed => write!(f, "ring buffer is closed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 写入失败时返回的错误，携带未能写入的值，调用方可以据此重试而不必提前克隆数据
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    /// 队列已满
+    Full(T),
+    /// 读取者已经被释放
+    Disconnected(T),
+    /// 写入端已经关闭
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// 取回未能写入的值
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) | TrySendError::Disconnected(value) | TrySendError::Closed(value) => value,
+        }
+    }
+
+    /// 不携带数据的错误种类
+    #[inline]
+    pub fn kind(&self) -> Error {
+        match self {
+            TrySendError::Full(_) => Error::Full,
+            TrySendError::Disconnected(_) => Error::Disconnected,
+            TrySendError::Closed(_) => Error::Closed,
+        }
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        matches!(self, TrySendError::Full(_))
+    }
+
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, TrySendError::Disconnected(_))
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TrySendError::Closed(_))
+    }
+}
+
+impl<T> std::fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "Full(..)"),
+            TrySendError::Disconnected(_) => write!(f, "Disconnected(..)"),
+            TrySendError::Closed(_) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+impl<T> std::fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "sending on a full ring buffer"),
+            TrySendError::Disconnected(_) => write!(f, "sending on a disconnected ring buffer"),
+            TrySendError::Closed(_) => write!(f, "sending on a closed ring buffer"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+impl<T> From<TrySendError<T>> for Error {
+    #[inline]
+    fn from(err: TrySendError<T>) -> Self {
+        err.kind()
+    }
+}
+
+impl<T> From<SendError<T>> for TrySendError<T> {
+    #[inline]
+    fn from(err: SendError<T>) -> Self {
+        match err {
+            SendError::Disconnected(value) => TrySendError::Disconnected(value),
+            SendError::Closed(value) => TrySendError::Closed(value),
+        }
+    }
+}
+
+/// 限时写入失败时返回的错误，携带未能写入的值
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendTimeoutError<T> {
+    /// 等待超时，队列仍然是满的
+    Timeout(T),
+    /// 读取者已经被释放
+    Disconnected(T),
+    /// 写入端已经关闭
+    Closed(T),
+}
+
+impl<T> SendTimeoutError<T> {
+    /// 取回未能写入的值
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            SendTimeoutError::Timeout(value) | SendTimeoutError::Disconnected(value) | SendTimeoutError::Closed(value) => value,
+        }
+    }
+
+    /// 不携带数据的错误种类
+    #[inline]
+    pub fn kind(&self) -> Error {
+        match self {
+            SendTimeoutError::Timeout(_) => Error::Timeout,
+            SendTimeoutError::Disconnected(_) => Error::Disconnected,
+            SendTimeoutError::Closed(_) => Error::Closed,
+        }
+    }
+
+    #[inline]
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, SendTimeoutError::Timeout(_))
+    }
+
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, SendTimeoutError::Disconnected(_))
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        matches!(self, SendTimeoutError::Closed(_))
+    }
+}
+
+impl<T> std::fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => write!(f, "Timeout(..)"),
+            SendTimeoutError::Disconnected(_) => write!(f, "Disconnected(..)"),
+            SendTimeoutError::Closed(_) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+impl<T> std::fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => write!(f, "timed out waiting on a full ring buffer"),
+            SendTimeoutError::Disconnected(_) => write!(f, "sending on a disconnected ring buffer"),
+            SendTimeoutError::Closed(_) => write!(f, "sending on a closed ring buffer"),
+        }
+    }
+}
+
+impl<T> std::error::Error for SendTimeoutError<T> {}
+
+impl<T> From<SendTimeoutError<T>> for Error {
+    #[inline]
+    fn from(err: SendTimeoutError<T>) -> Self {
+        err.kind()
+    }
+}
+
+impl<T> From<SendError<T>> for SendTimeoutError<T> {
+    #[inline]
+    fn from(err: SendError<T>) -> Self {
+        match err {
+            SendError::Disconnected(value) => SendTimeoutError::Disconnected(value),
+            SendError::Closed(value) => SendTimeoutError::Closed(value),
+        }
+    }
+}
+
+/// 阻塞写入失败时返回的错误，只会在读取者已经被释放或者写入端已经关闭时出现，携带未能写入的值
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendError<T> {
+    /// 读取者已经被释放
+    Disconnected(T),
+    /// 写入端已经关闭
+    Closed(T),
+}
+
+impl<T> SendError<T> {
+    /// 取回未能写入的值
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            SendError::Disconnected(value) | SendError::Closed(value) => value,
+        }
+    }
+
+    /// 不携带数据的错误种类
+    #[inline]
+    pub fn kind(&self) -> Error {
+        match self {
+            SendError::Disconnected(_) => Error::Disconnected,
+            SendError::Closed(_) => Error::Closed,
+        }
+    }
+
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, SendError::Disconnected(_))
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        matches!(self, SendError::Closed(_))
+    }
+}
+
+impl<T> std::fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::Disconnected(_) => write!(f, "Disconnected(..)"),
+            SendError::Closed(_) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+impl<T> std::fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::Disconnected(_) => write!(f, "sending on a disconnected ring buffer"),
+            SendError::Closed(_) => write!(f, "sending on a closed ring buffer"),
+        }
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+impl<T> From<SendError<T>> for Error {
+    #[inline]
+    fn from(err: SendError<T>) -> Self {
+        err.kind()
+    }
+}
+
+/// 运行时指定的容量无效时返回的错误，携带请求的容量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError(pub usize);
+
+impl std::fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid ring buffer capacity {}", self.0)
+    }
+}
+
+impl std::error::Error for CapacityError {}
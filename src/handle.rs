@@ -0,0 +1,915 @@
+//! 队列的读写句柄，阻塞、超时、异步等待、关闭以及守卫的逻辑只在这里实现一次，
+//! 各个队列通过 `Queue` 给出自己的索引协议，`queue`、`lockfree_mpsc`、`lockfree_spmc` 中的句柄都是这里的类型，
+//! 所以切换队列只需要换一个构造函数。
+
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+use crate::error::{Error, SendError, SendTimeoutError, TrySendError};
+use crate::signal::{self, Register};
+use crate::storage::{Defaulted, Storage};
+
+/// 队列的索引协议，由句柄调用
+///
+/// 写入一侧的方法（push、reserve、slot、commit、abort）同一时刻只能由一个线程调用，实现了 `MultiSender` 的队列除外；
+/// 读取一侧的方法（pop、claim、claimed、consume、release）同一时刻只能由一个线程调用，实现了 `MultiReceiver` 的队列除外。
+///
+/// # Safety
+/// 实现者必须保证按照上面的约定调用时，同一个槽位不会同时被两个线程访问，
+/// 并且 Item: Send 时队列可以在线程之间共享
+pub unsafe trait Queue {
+    /// 队列中的元素类型
+    type Item;
+
+    /// 槽位的存储策略
+    type Storage: Storage<Self::Item>;
+
+    /// 当前队列中的元素个数
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 最多可容纳的元素个数
+    fn capacity(&self) -> usize;
+
+    /// 写入者是否需要等待空位
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.len() >= self.capacity()
+    }
+
+    /// 队列已满时是否覆盖最早的数据
+    #[inline]
+    fn is_overwrite(&self) -> bool {
+        false
+    }
+
+    /// 最近一次取出的数据之前被覆盖的元素个数，只能由读取者调用
+    #[inline]
+    fn missed(&self) -> usize {
+        0
+    }
+
+    /// 是否还有没关闭的写入者
+    fn has_senders(&self) -> bool;
+
+    /// 是否还有没释放的读取者
+    fn has_receivers(&self) -> bool;
+
+    /// 一个写入者关闭，每个写入者只调用一次
+    fn release_sender(&self);
+
+    /// 一个读取者被释放，每个读取者只调用一次
+    fn release_receiver(&self);
+
+    /// 登记等待空位的写入者
+    fn register_tx(&self, waker: &Waker);
+
+    /// 腾出空位之后唤醒写入者
+    fn notify_tx(&self);
+
+    /// 登记等待数据的读取者
+    fn register_rx(&self, waker: &Waker);
+
+    /// 写入数据之后唤醒读取者
+    fn notify_rx(&self);
+
+    /// 写入数据，队列已满时将值原样返回
+    ///
+    /// # Safety
+    /// 见上面写入一侧的约定
+    unsafe fn push_unchecked(&self, value: Self::Item) -> Result<(), Self::Item>;
+
+    /// 预留一个空槽，返回它的索引，队列已满时返回 None，空槽在提交之前对读取者不可见
+    ///
+    /// # Safety
+    /// 见上面写入一侧的约定
+    unsafe fn reserve_unchecked(&self) -> Option<usize>;
+
+    /// 预留的槽位
+    ///
+    /// # Safety
+    /// head 必须是 `reserve_unchecked` 返回的还没有提交或放弃的槽位
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn slot_unchecked(&self, head: usize) -> &mut <Self::Storage as Storage<Self::Item>>::Slot;
+
+    /// 提交预留的槽位，读取者随后可以取出
+    ///
+    /// # Safety
+    /// head 必须是 `reserve_unchecked` 返回的还没有提交或放弃的槽位，槽位中已经写入数据
+    unsafe fn commit_unchecked(&self, head: usize);
+
+    /// 放弃预留的槽位，槽位中没有数据
+    ///
+    /// # Safety
+    /// head 必须是 `reserve_unchecked` 返回的还没有提交或放弃的槽位
+    #[inline]
+    unsafe fn abort_unchecked(&self, head: usize) {
+        let _ = head;
+    }
+
+    /// 取出最早的数据，队列为空时返回 None
+    ///
+    /// # Safety
+    /// 见上面读取一侧的约定
+    unsafe fn pop_unchecked(&self) -> Option<Self::Item>;
+
+    /// 占用最早的数据，返回它的索引，队列为空时返回 None，释放之前写入者不会丢弃它，其他读取者也不会取走它
+    ///
+    /// # Safety
+    /// 见上面读取一侧的约定
+    unsafe fn claim_unchecked(&self) -> Option<usize>;
+
+    /// 占用的数据
+    ///
+    /// # Safety
+    /// tail 必须是 `claim_unchecked` 返回的还没有取出或释放的槽位
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn claimed_unchecked(&self, tail: usize) -> &mut Self::Item;
+
+    /// 取出占用的数据
+    ///
+    /// # Safety
+    /// tail 必须是 `claim_unchecked` 返回的还没有取出或释放的槽位
+    unsafe fn consume_unchecked(&self, tail: usize) -> Self::Item;
+
+    /// 放弃占用，数据留在队列中
+    ///
+    /// # Safety
+    /// tail 必须是 `claim_unchecked` 返回的还没有取出或释放的槽位
+    unsafe fn release_unchecked(&self, tail: usize);
+
+    /// 批量写入，返回写入的元素个数
+    ///
+    /// # Safety
+    /// 见上面写入一侧的约定
+    unsafe fn push_slice_unchecked(&self, values: &[Self::Item]) -> usize
+    where
+        Self::Item: Copy,
+    {
+        values.iter().take_while(|value| self.push_unchecked(**value).is_ok()).count()
+    }
+
+    /// 批量取出，最多填满 out，返回取出的元素个数
+    ///
+    /// # Safety
+    /// 见上面读取一侧的约定
+    unsafe fn pop_into_unchecked(&self, out: &mut [Self::Item]) -> usize
+    where
+        Self::Item: Copy,
+    {
+        let mut n = 0;
+        while n < out.len() {
+            match self.pop_unchecked() {
+                Some(value) => out[n] = value,
+                None => break,
+            }
+            n += 1;
+        }
+        n
+    }
+}
+
+/// 可以有多个写入者的队列，`Sender` 据此实现 Clone
+///
+/// # Safety
+/// 写入一侧的方法必须可以被多个线程同时调用
+pub unsafe trait MultiSender: Queue {
+    /// 新增一个写入者
+    fn acquire_sender(&self);
+}
+
+/// 可以有多个读取者的队列，`Receiver` 据此实现 Clone
+///
+/// # Safety
+/// 读取一侧的方法必须可以被多个线程同时调用
+pub unsafe trait MultiReceiver: Queue {
+    /// 新增一个读取者
+    fn acquire_receiver(&self);
+}
+
+/// 在写入一侧等待
+struct Tx<'a, Q>(&'a Q);
+
+impl<Q: Queue> Register for Tx<'_, Q> {
+    #[inline]
+    fn register(&self, waker: &Waker) {
+        self.0.register_tx(waker)
+    }
+}
+
+/// 在读取一侧等待
+struct Rx<'a, Q>(&'a Q);
+
+impl<Q: Queue> Register for Rx<'_, Q> {
+    #[inline]
+    fn register(&self, waker: &Waker) {
+        self.0.register_rx(waker)
+    }
+}
+
+/// 第一次尝试失败并且写入者都已经关闭时再试一次，写入者在关闭之前写入的数据对这里可见，
+/// 仍然失败并且队列中没有被其他读取者占用的数据时才返回 `Error::Disconnected`
+fn disconnected<Q: Queue, R>(inner: &Q, mut f: impl FnMut() -> Option<R>) -> Result<R, Error> {
+    if let Some(r) = f() {
+        return Ok(r);
+    }
+    if inner.has_senders() {
+        return Err(Error::Empty);
+    }
+    match f() {
+        Some(r) => Ok(r),
+        None if inner.is_empty() => Err(Error::Disconnected),
+        None => Err(Error::Empty),
+    }
+}
+
+/// 写入者
+/// 同一时刻只能有一个线程使用写入者，不能通过 &Sender 在线程之间共享，所以不实现 Sync
+pub struct Sender<Q: Queue> {
+    inner: Arc<Q>,
+    /// 是否已经通过 `close` 关闭，关闭之后不再计入存活的写入者
+    closed: bool,
+    /// 不自动实现 Send 和 Sync，由下面的 unsafe impl 显式给出
+    _marker: PhantomData<*mut ()>,
+}
+
+/// 写入者可以整体移动到其他线程，随后写入的数据由读取者在另一个线程取出，释放时也可能在这里析构剩余的数据，
+/// 所以要求 Item: Send
+unsafe impl<Q: Queue> Send for Sender<Q> where Q::Item: Send {}
+
+impl<Q: Queue> Sender<Q> {
+    #[inline]
+    pub(crate) fn new(inner: Arc<Q>) -> Self {
+        Self {
+            inner,
+            closed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 底层的队列，供各个队列实现自己特有的方法
+    #[inline]
+    #[cfg_attr(loom, allow(dead_code))]
+    pub(crate) fn queue(&self) -> &Q {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// 当前队列中的元素个数
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// 最多可容纳的元素个数
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// 剩余可写入的元素个数
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity().saturating_sub(self.len())
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// 读取者是否都已经被释放
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        !self.inner.has_receivers()
+    }
+
+    /// 当前写入者是否已经通过 `close` 关闭
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// 关闭当前写入者，之后的写入都返回 Closed，和释放写入者效果相同：
+    /// 所有写入者都关闭之后，读取者取完剩余的数据收到 `Error::Disconnected`
+    #[inline]
+    pub fn close(&mut self) {
+        if !self.closed {
+            self.closed = true;
+            self.inner.release_sender();
+            self.inner.notify_rx();
+        }
+    }
+
+    /// 写入者已经关闭时返回 `Error::Closed`，读取者都已经被释放时返回 `Error::Disconnected`
+    #[inline]
+    pub(crate) fn check_open(&self) -> Result<(), Error> {
+        if self.closed {
+            Err(Error::Closed)
+        } else if self.is_disconnected() {
+            Err(Error::Disconnected)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 是否为覆盖模式，覆盖模式下写入总是成功，队列已满时丢弃最早的数据
+    #[inline]
+    pub fn is_overwrite(&self) -> bool {
+        self.inner.is_overwrite()
+    }
+
+    /// 写入数据，不会阻塞，队列已满、读取者都已经被释放或者写入者已经关闭时将值原样返回
+    #[inline]
+    pub fn try_push(&mut self, value: Q::Item) -> Result<(), TrySendError<Q::Item>> {
+        if self.closed {
+            return Err(TrySendError::Closed(value));
+        }
+        if self.is_disconnected() {
+            return Err(TrySendError::Disconnected(value));
+        }
+        // 写入需要 &mut self，只有实现了 MultiSender 的队列才会有多个写入者同时写入
+        unsafe { self.inner.push_unchecked(value) }.map_err(TrySendError::Full)?;
+        self.inner.notify_rx();
+        Ok(())
+    }
+
+    /// 同 `try_push`
+    #[inline]
+    pub fn push(&mut self, value: Q::Item) -> Result<(), TrySendError<Q::Item>> {
+        self.try_push(value)
+    }
+
+    /// 预留下一个空槽，直接在槽位中写入数据，`WriteGuard::commit` 之后读取者才能看到，
+    /// 没有提交就被释放时放弃写入。队列已满时返回 `Error::Full`，读取者都已经被释放时返回 `Error::Disconnected`，
+    /// 写入者已经关闭时返回 `Error::Closed`
+    /// 覆盖模式下预留时就会丢弃最早的数据，只有最早的数据被 `Peek` 或 `ReadGuard` 占用时才返回 `Error::Full`
+    pub fn reserve(&mut self) -> Result<WriteGuard<'_, Q>, Error> {
+        self.check_open()?;
+        // 写入需要 &mut self，guard 存活期间当前写入者不会有其它写入
+        match unsafe { self.inner.reserve_unchecked() } {
+            Some(head) => Ok(WriteGuard {
+                inner: &self.inner,
+                head,
+                written: <Q::Storage as Storage<Q::Item>>::PREFILLED,
+                committed: false,
+                _marker: PhantomData,
+            }),
+            None => Err(Error::Full),
+        }
+    }
+
+    /// 写入数据，队列已满时挂起当前线程，直到读取者取走数据腾出空位，读取者都已经被释放或者写入者已经关闭时将值原样返回
+    pub fn send(&mut self, value: Q::Item) -> Result<(), SendError<Q::Item>> {
+        match self.send_until(value, None) {
+            Ok(()) => Ok(()),
+            Err(SendTimeoutError::Disconnected(value)) => Err(SendError::Disconnected(value)),
+            Err(SendTimeoutError::Closed(value)) => Err(SendError::Closed(value)),
+            Err(SendTimeoutError::Timeout(_)) => unreachable!("sending without deadline never times out"),
+        }
+    }
+
+    /// 写入数据，队列已满时最多等待 timeout，超时将值原样返回
+    #[inline]
+    pub fn send_timeout(&mut self, value: Q::Item, timeout: Duration) -> Result<(), SendTimeoutError<Q::Item>> {
+        self.send_until(value, Instant::now().checked_add(timeout))
+    }
+
+    /// 写入数据，队列已满时最多等待到 deadline，超时将值原样返回
+    #[inline]
+    pub fn send_deadline(&mut self, value: Q::Item, deadline: Instant) -> Result<(), SendTimeoutError<Q::Item>> {
+        self.send_until(value, Some(deadline))
+    }
+
+    fn send_until(&mut self, value: Q::Item, deadline: Option<Instant>) -> Result<(), SendTimeoutError<Q::Item>> {
+        if self.closed {
+            return Err(SendTimeoutError::Closed(value));
+        }
+        let inner = &*self.inner;
+        let mut value = Some(value);
+        let res = signal::wait(&Tx(inner), deadline, || {
+            let v = value.take()?;
+            if !inner.has_receivers() {
+                return Some(Err(SendTimeoutError::Disconnected(v)));
+            }
+            match unsafe { inner.push_unchecked(v) } {
+                Ok(()) => Some(Ok(())),
+                Err(v) => {
+                    value = Some(v);
+                    None
+                }
+            }
+        });
+        match (res, value) {
+            (Some(res), _) => {
+                if res.is_ok() {
+                    inner.notify_rx();
+                }
+                res
+            }
+            (None, Some(value)) => Err(SendTimeoutError::Timeout(value)),
+            (None, None) => unreachable!("value is kept until it is sent"),
+        }
+    }
+
+    /// 异步等待队列出现空位，队列已满时登记当前任务的 Waker，读取者取走数据后唤醒，
+    /// 读取者都已经被释放时返回 `Error::Disconnected`，写入者已经关闭时返回 `Error::Closed`
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        if let Err(err) = self.check_open() {
+            return Poll::Ready(Err(err));
+        }
+        let inner = &*self.inner;
+        signal::poll(&Tx(inner), cx, || {
+            if !inner.has_receivers() {
+                Some(Err(Error::Disconnected))
+            } else if inner.is_overwrite() || !inner.is_full() {
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// 异步写入数据，队列已满时等待读取者腾出空位，读取者都已经被释放或者写入者已经关闭时将值原样返回
+    pub async fn send_async(&mut self, mut value: Q::Item) -> Result<(), SendError<Q::Item>> {
+        loop {
+            match std::future::poll_fn(|cx| self.poll_ready(cx)).await {
+                Err(Error::Closed) => return Err(SendError::Closed(value)),
+                Err(_) => return Err(SendError::Disconnected(value)),
+                Ok(()) => {}
+            }
+            // 其他写入者可能抢先占用了空位，此时重新等待
+            match self.try_push(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(v)) => value = v,
+                Err(TrySendError::Disconnected(v)) => return Err(SendError::Disconnected(v)),
+                Err(TrySendError::Closed(v)) => return Err(SendError::Closed(v)),
+            }
+        }
+    }
+}
+
+impl<Q: Queue> Sender<Q>
+where
+    Q::Item: Copy,
+{
+    /// 批量写入，只写入队列能容纳的部分，返回写入的元素个数，读取者都已经被释放或者写入者已经关闭时返回0
+    pub fn push_slice(&mut self, values: &[Q::Item]) -> usize {
+        if self.check_open().is_err() {
+            return 0;
+        }
+        let n = unsafe { self.inner.push_slice_unchecked(values) };
+        if n > 0 {
+            self.inner.notify_rx();
+        }
+        n
+    }
+}
+
+/// 复制出的写入者和原来的写入者一起计入存活的写入者，复制一个已经关闭的写入者得到的也是已经关闭的写入者
+impl<Q: MultiSender> Clone for Sender<Q> {
+    fn clone(&self) -> Self {
+        if !self.closed {
+            self.inner.acquire_sender();
+        }
+        Self {
+            inner: self.inner.clone(),
+            closed: self.closed,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Q: Queue> Drop for Sender<Q> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// 读取者
+/// 同一时刻只能有一个线程使用读取者，不能通过 &Receiver 在线程之间共享，所以不实现 Sync
+pub struct Receiver<Q: Queue> {
+    inner: Arc<Q>,
+    /// 不自动实现 Send 和 Sync，由下面的 unsafe impl 显式给出
+    _marker: PhantomData<*mut ()>,
+}
+
+/// 读取者取出的数据来自写入者所在的线程，所以要求 Item: Send
+unsafe impl<Q: Queue> Send for Receiver<Q> where Q::Item: Send {}
+
+impl<Q: Queue> Receiver<Q> {
+    #[inline]
+    pub(crate) fn new(inner: Arc<Q>) -> Self {
+        Self {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 底层的队列，供各个队列实现自己特有的方法
+    #[inline]
+    #[cfg_attr(loom, allow(dead_code))]
+    pub(crate) fn queue(&self) -> &Q {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// 当前队列中的元素个数
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// 最多可容纳的元素个数
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// 剩余可写入的元素个数
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity().saturating_sub(self.len())
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// 写入者是否都已经关闭，关闭之后队列中剩余的数据仍然可以取出
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        !self.inner.has_senders()
+    }
+
+    /// 是否为覆盖模式
+    #[inline]
+    pub fn is_overwrite(&self) -> bool {
+        self.inner.is_overwrite()
+    }
+
+    /// 最近一次取出的数据之前被覆盖的元素个数，即上一次取出之后错过的数据个数，非覆盖模式下总是0
+    #[inline]
+    pub fn missed(&self) -> usize {
+        // 读取需要 &mut self，这里不会和取出同时发生
+        self.inner.missed()
+    }
+
+    /// 查看最早的数据但不取出，队列为空时返回 None
+    /// 返回的 `Peek` 存活期间其他读取者不会取走被查看的数据，覆盖模式下写入者也不会丢弃它，
+    /// 此时队列已满的话写入者丢弃新写入的数据，`Peek` 释放之后写入者恢复丢弃最早的数据
+    #[inline]
+    pub fn peek(&mut self) -> Option<Peek<'_, Q>> {
+        let tail = unsafe { self.inner.claim_unchecked()? };
+        Some(Peek {
+            inner: &self.inner,
+            tail,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 同 `peek`，返回的 `ReadGuard` 可以原地修改队列中的数据，没有提交就被释放时数据留在队列中
+    #[inline]
+    pub fn peek_mut(&mut self) -> Option<ReadGuard<'_, Q>> {
+        let tail = unsafe { self.inner.claim_unchecked()? };
+        Some(ReadGuard {
+            inner: &self.inner,
+            tail,
+            committed: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 占用最早的数据，处理成功之后通过 `ReadGuard::commit` 取出，没有提交就被释放时数据留在队列中，
+    /// 队列为空时返回 `Error::Empty`，写入者都已经关闭并且队列中的数据已经取完时返回 `Error::Disconnected`
+    pub fn pop_guard(&mut self) -> Result<ReadGuard<'_, Q>, Error> {
+        let inner = &*self.inner;
+        let tail = disconnected(inner, || unsafe { inner.claim_unchecked() })?;
+        Ok(ReadGuard {
+            inner,
+            tail,
+            committed: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 取出数据，不会阻塞，队列为空时返回 `Error::Empty`，
+    /// 写入者都已经关闭并且队列中的数据已经取完时返回 `Error::Disconnected`
+    #[inline]
+    pub fn try_pop(&mut self) -> Result<Q::Item, Error> {
+        let inner = &*self.inner;
+        // 读取需要 &mut self，只有实现了 MultiReceiver 的队列才会有多个读取者同时读取
+        let res = disconnected(inner, || unsafe { inner.pop_unchecked() });
+        if res.is_ok() {
+            inner.notify_tx();
+        }
+        res
+    }
+
+    /// 同 `try_pop`
+    #[inline]
+    pub fn pop(&mut self) -> Result<Q::Item, Error> {
+        self.try_pop()
+    }
+
+    /// 取出数据，队列为空时挂起当前线程，直到写入者写入新的数据
+    #[inline]
+    pub fn recv(&mut self) -> Result<Q::Item, Error> {
+        self.recv_until(None)
+    }
+
+    /// 取出数据，队列为空时最多等待 timeout，超时返回 `Error::Timeout`
+    #[inline]
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Q::Item, Error> {
+        self.recv_until(Instant::now().checked_add(timeout))
+    }
+
+    /// 取出数据，队列为空时最多等待到 deadline，超时返回 `Error::Timeout`
+    #[inline]
+    pub fn recv_deadline(&mut self, deadline: Instant) -> Result<Q::Item, Error> {
+        self.recv_until(Some(deadline))
+    }
+
+    fn recv_until(&mut self, deadline: Option<Instant>) -> Result<Q::Item, Error> {
+        let inner = &*self.inner;
+        let res = signal::wait(&Rx(inner), deadline, || {
+            match disconnected(inner, || unsafe { inner.pop_unchecked() }) {
+                Err(Error::Empty) => None,
+                res => Some(res),
+            }
+        }).unwrap_or(Err(Error::Timeout));
+        if res.is_ok() {
+            inner.notify_tx();
+        }
+        res
+    }
+
+    /// 异步取出数据，队列为空时登记当前任务的 Waker，写入者写入新的数据后唤醒
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<Q::Item, Error>> {
+        let inner = &*self.inner;
+        let res = std::task::ready!(signal::poll(&Rx(inner), cx, || {
+            match disconnected(inner, || unsafe { inner.pop_unchecked() }) {
+                Err(Error::Empty) => None,
+                res => Some(res),
+            }
+        }));
+        if res.is_ok() {
+            inner.notify_tx();
+        }
+        Poll::Ready(res)
+    }
+
+    /// 异步取出数据，队列为空时等待写入者写入新的数据
+    pub async fn recv_async(&mut self) -> Result<Q::Item, Error> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+}
+
+impl<Q: Queue> Receiver<Q>
+where
+    Q::Item: Copy,
+{
+    /// 批量取出，最多填满 out，返回取出的元素个数
+    pub fn pop_into(&mut self, out: &mut [Q::Item]) -> usize {
+        let n = unsafe { self.inner.pop_into_unchecked(out) };
+        if n > 0 {
+            self.inner.notify_tx();
+        }
+        n
+    }
+}
+
+/// 复制出的读取者和原来的读取者竞争同一个队列，每个数据只会被其中一个取走
+impl<Q: MultiReceiver> Clone for Receiver<Q> {
+    fn clone(&self) -> Self {
+        self.inner.acquire_receiver();
+        Self::new(self.inner.clone())
+    }
+}
+
+impl<Q: Queue> Drop for Receiver<Q> {
+    fn drop(&mut self) {
+        self.inner.release_receiver();
+        self.inner.notify_tx();
+    }
+}
+
+/// `Sender::reserve` 预留的槽位，`Defaulted` 存储时槽位中已经有默认值，可以解引用原地修改
+pub struct WriteGuard<'a, Q: Queue> {
+    inner: &'a Q,
+    head: usize,
+    /// 槽位中是否有还没有提交的数据，`Defaulted` 存储时空槽本身就是有效的数据
+    written: bool,
+    committed: bool,
+    /// 不通过 &Q 自动实现 Send 和 Sync，由下面的 unsafe impl 显式给出
+    _marker: PhantomData<*mut ()>,
+}
+
+/// 守卫只是借用了写入者，移动到其他线程写入的数据仍由读取者取出，要求 Item: Send
+unsafe impl<Q: Queue> Send for WriteGuard<'_, Q> where Q::Item: Send {}
+/// 通过 &WriteGuard 只能得到 &Item，要求 Item: Sync，而不是队列共享时的 Item: Send
+unsafe impl<Q: Queue> Sync for WriteGuard<'_, Q> where Q::Item: Sync {}
+
+impl<Q: Queue> WriteGuard<'_, Q> {
+    /// 直接写入槽位，返回槽位中数据的引用，提交之前还可以继续修改
+    #[inline]
+    pub fn write(&mut self, value: Q::Item) -> &mut Q::Item {
+        let slot = unsafe { self.inner.slot_unchecked(self.head) };
+        if self.written {
+            let data = unsafe { <Q::Storage as Storage<Q::Item>>::get_mut(slot) };
+            *data = value;
+            return data;
+        }
+        self.written = true;
+        unsafe { <Q::Storage as Storage<Q::Item>>::write(slot, value) }
+    }
+
+    /// 已经写入的数据
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut Q::Item> {
+        if !self.written {
+            return None;
+        }
+        Some(unsafe { <Q::Storage as Storage<Q::Item>>::get_mut(self.inner.slot_unchecked(self.head)) })
+    }
+
+    /// 提交写入的数据，读取者随后可以取出，没有写入数据时等同于放弃
+    pub fn commit(mut self) {
+        if self.written {
+            self.committed = true;
+            unsafe { self.inner.commit_unchecked(self.head) };
+            self.inner.notify_rx();
+        }
+    }
+}
+
+impl<Q: Queue> Drop for WriteGuard<'_, Q> {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        if self.written {
+            // 放弃写入，槽位还没有发布，直接析构写入的数据，`Defaulted` 存储时恢复为默认值，
+            // 避免下一次预留看到写了一半的数据
+            unsafe { <Q::Storage as Storage<Q::Item>>::clear(self.inner.slot_unchecked(self.head)) };
+        }
+        unsafe { self.inner.abort_unchecked(self.head) };
+    }
+}
+
+impl<Q: Queue<Storage = Defaulted>> Deref for WriteGuard<'_, Q>
+where
+    Q::Item: Default,
+{
+    type Target = Q::Item;
+
+    #[inline]
+    fn deref(&self) -> &Q::Item {
+        unsafe { self.inner.slot_unchecked(self.head) }
+    }
+}
+
+impl<Q: Queue<Storage = Defaulted>> DerefMut for WriteGuard<'_, Q>
+where
+    Q::Item: Default,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Q::Item {
+        unsafe { self.inner.slot_unchecked(self.head) }
+    }
+}
+
+/// `Receiver::pop_guard` 或 `Receiver::peek_mut` 占用的最早的数据，解引用得到数据本身
+pub struct ReadGuard<'a, Q: Queue> {
+    inner: &'a Q,
+    tail: usize,
+    committed: bool,
+    _marker: PhantomData<*mut ()>,
+}
+
+unsafe impl<Q: Queue> Send for ReadGuard<'_, Q> where Q::Item: Send {}
+unsafe impl<Q: Queue> Sync for ReadGuard<'_, Q> where Q::Item: Sync {}
+
+impl<Q: Queue> ReadGuard<'_, Q> {
+    /// 从队列中取出数据
+    pub fn commit(mut self) -> Q::Item {
+        self.committed = true;
+        let res = unsafe { self.inner.consume_unchecked(self.tail) };
+        self.inner.notify_tx();
+        res
+    }
+}
+
+impl<Q: Queue> Deref for ReadGuard<'_, Q> {
+    type Target = Q::Item;
+
+    #[inline]
+    fn deref(&self) -> &Q::Item {
+        unsafe { self.inner.claimed_unchecked(self.tail) }
+    }
+}
+
+impl<Q: Queue> DerefMut for ReadGuard<'_, Q> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Q::Item {
+        unsafe { self.inner.claimed_unchecked(self.tail) }
+    }
+}
+
+impl<Q: Queue> Drop for ReadGuard<'_, Q> {
+    fn drop(&mut self) {
+        if !self.committed {
+            // 数据留在队列中，下一次读取还能拿到
+            unsafe { self.inner.release_unchecked(self.tail) };
+        }
+    }
+}
+
+/// `Receiver::peek` 查看的最早的数据，释放时解除占用，数据留在队列中
+pub struct Peek<'a, Q: Queue> {
+    inner: &'a Q,
+    tail: usize,
+    _marker: PhantomData<*mut ()>,
+}
+
+unsafe impl<Q: Queue> Send for Peek<'_, Q> where Q::Item: Send {}
+unsafe impl<Q: Queue> Sync for Peek<'_, Q> where Q::Item: Sync {}
+
+impl<Q: Queue> Deref for Peek<'_, Q> {
+    type Target = Q::Item;
+
+    #[inline]
+    fn deref(&self) -> &Q::Item {
+        unsafe { self.inner.claimed_unchecked(self.tail) }
+    }
+}
+
+impl<Q: Queue> Drop for Peek<'_, Q> {
+    fn drop(&mut self) {
+        unsafe { self.inner.release_unchecked(self.tail) };
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<Q: Queue> futures_core::Stream for Receiver<Q> {
+    type Item = Q::Item;
+
+    /// 写入者都关闭并且数据取完之后结束
+    fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Q::Item>> {
+        match self.get_mut().poll_recv(cx) {
+            Poll::Ready(Ok(value)) => Poll::Ready(Some(value)),
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<Q: Queue> futures_sink::Sink<Q::Item> for Sender<Q> {
+    type Error = Error;
+
+    fn poll_ready(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.get_mut().poll_ready(cx)
+    }
+
+    /// 其他写入者可能在 poll_ready 之后抢先占用空位，此时返回 `Error::Full`
+    fn start_send(self: std::pin::Pin<&mut Self>, item: Q::Item) -> Result<(), Error> {
+        self.get_mut().try_push(item).map_err(Error::from)
+    }
+
+    /// 写入的数据立即对读取者可见，不需要刷新
+    fn poll_flush(self: std::pin::Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// 关闭当前写入者，所有写入者都关闭之后，读取者的 Stream 取完剩余的数据结束
+    fn poll_close(self: std::pin::Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.get_mut().close();
+        Poll::Ready(Ok(()))
+    }
+}
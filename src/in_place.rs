@@ -0,0 +1,35 @@
+//! 直接在目标内存中逐个字段初始化，槽位数组很大时不会先在栈上构造整个对象再移动
+
+use std::mem::MaybeUninit;
+use std::sync::Arc;
+
+/// 直接在 Arc 的堆内存中初始化
+///
+/// # Safety
+/// init 必须初始化 T 的全部字段
+pub(crate) unsafe fn new_arc<T>(init: impl FnOnce(*mut T)) -> Arc<T> {
+    let mut arc = Arc::<T>::new_uninit();
+    init(Arc::get_mut(&mut arc).expect("newly created arc is unique").as_mut_ptr());
+    arc.assume_init()
+}
+
+/// 按值返回，和 `new_arc` 共用同一份初始化代码
+///
+/// # Safety
+/// init 必须初始化 T 的全部字段
+pub(crate) unsafe fn new_value<T>(init: impl FnOnce(*mut T)) -> T {
+    let mut value = MaybeUninit::<T>::uninit();
+    init(value.as_mut_ptr());
+    value.assume_init()
+}
+
+/// 逐个初始化数组元素，每次只在栈上构造一个元素
+///
+/// # Safety
+/// ptr 必须指向可写的数组内存，原有内容不会被释放
+pub(crate) unsafe fn init_array<S, const N: usize>(ptr: *mut [S; N], mut f: impl FnMut(usize) -> S) {
+    let ptr = ptr.cast::<S>();
+    for i in 0..N {
+        ptr.add(i).write(f(i));
+    }
+}
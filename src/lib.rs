@@ -1,8 +1,20 @@
-#![feature(get_mut_unchecked)]
-
+pub mod queue;
+pub mod handle;
+pub mod value;
+pub mod storage;
+pub mod error;
 pub mod lockfree_queue;
 pub mod lockfree_value;
+pub mod lockfree_mpsc;
+pub mod lockfree_spmc;
+pub mod lockfree_mpmc;
+pub mod lockfree_broadcast;
+pub mod lockfree_heap;
+pub mod channel;
 pub mod default;
+mod signal;
+mod in_place;
+mod sync;
 
-pub use lockfree_value::LockFreeValue;
-pub use lockfree_queue::RingBuffer;
\ No newline at end of file
+pub use value::LockFreeValue;
+pub use queue::RingBuffer;
\ No newline at end of file
@@ -0,0 +1,417 @@
+use std::cell::UnsafeCell;
+use std::ptr::addr_of_mut;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use crossbeam_utils::CachePadded;
+use crate::error::{Error, SendError, SendTimeoutError, TrySendError};
+use crate::signal::{self, Signal, WaitList};
+use crate::in_place::{self, init_array};
+
+type Cursor = Arc<CachePadded<AtomicUsize>>;
+
+/// 广播环形缓冲区，写入的每个数据都会被所有订阅者读到
+/// 每个订阅者持有自己的读取游标，写入者受最慢的游标限制，不会覆盖还有订阅者没读到的数据。
+/// 写入者把最慢游标缓存在 gate 中，只有看起来写满时才加锁重新计算，所以写入的快速路径没有锁。
+/// 新的订阅者可以从仍然保留的最早的数据开始读取，此时在订阅的锁中把 gate 降到它的起点，
+/// gate 的写入和写入者发布 head 都是 SeqCst，订阅者写入 gate 之后再检查 head，
+/// 要么写入者在复用下一个槽位之前看到降低后的 gate，要么订阅者看到 head 变化之后重新选择起点。
+/// 索引单调递增，访问槽位时再取模，可以放满全部 SIZE 个元素。
+/// 数据只被克隆不被取走，槽位一直保留到被覆盖，所以这里保留 Option，由它记录槽位是否写入过并负责析构旧数据。
+#[derive(Debug)]
+pub struct RingBuffer<T, const SIZE: usize = 4> {
+    m_data: [UnsafeCell<Option<T>>; SIZE],
+    idx_head: CachePadded<AtomicUsize>,
+    /// 写入者最近一次计算得到的最慢游标，只在持有 cursors 锁时修改
+    gate: CachePadded<AtomicUsize>,
+    /// 所有订阅者的游标，只在订阅、退订和写入者重新计算 gate 时加锁
+    cursors: Mutex<Vec<Cursor>>,
+    /// 写入者等待最慢的订阅者
+    tx_signal: Signal,
+    /// 订阅者等待数据
+    rx_waiters: WaitList,
+    /// 写入者是否存活
+    tx_alive: AtomicBool,
+}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    /// 直接在 Arc 的堆内存中创建，槽位数组不经过栈
+    fn new_arc() -> Arc<Self> {
+        unsafe { in_place::new_arc(|ptr| Self::init(ptr)) }
+    }
+
+    /// 逐个字段初始化 ptr 指向的内存
+    ///
+    /// # Safety
+    /// ptr 必须指向可写的未初始化内存
+    unsafe fn init(ptr: *mut Self) {
+        const { assert!(SIZE.is_power_of_two(), "RingBuffer SIZE must be a power of two") };
+        init_array(addr_of_mut!((*ptr).m_data), |_| UnsafeCell::new(None));
+        addr_of_mut!((*ptr).idx_head).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).gate).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).cursors).write(Mutex::new(Vec::new()));
+        addr_of_mut!((*ptr).tx_signal).write(Signal::new());
+        addr_of_mut!((*ptr).rx_waiters).write(WaitList::new());
+        addr_of_mut!((*ptr).tx_alive).write(AtomicBool::new(true));
+    }
+}
+
+/// 槽位只会被唯一的写入者修改，并且写入者只修改所有游标都已经越过的槽位，
+/// 多个订阅者同时读取同一个槽位时只通过共享引用访问，所以要求 T: Sync
+unsafe impl<T: Send + Sync, const SIZE: usize> Sync for RingBuffer<T, SIZE> {}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    #[inline]
+    fn ring_idx(cur: usize) -> usize {
+        cur & (SIZE - 1)
+    }
+
+    #[inline]
+    fn lock_cursors(&self) -> MutexGuard<'_, Vec<Cursor>> {
+        self.cursors.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 仍然保留的最早的数据，索引从0开始，head 小于 SIZE 时还没有写满一圈。
+    /// gate 允许写入者写入 head 时，写入者可能正在覆盖 head 所在的槽位，也就是最早的数据，只能从下一个开始
+    #[inline]
+    fn oldest(head: usize, gate: usize) -> usize {
+        if head < SIZE {
+            0
+        } else if head.wrapping_sub(gate) < SIZE {
+            head - SIZE + 1
+        } else {
+            head - SIZE
+        }
+    }
+
+    /// 重新计算最慢游标，没有订阅者时不限制写入者
+    fn update_gate(&self, head: usize) -> usize {
+        let cursors = self.lock_cursors();
+        let gate = cursors
+            .iter()
+            .map(|c| c.load(Ordering::Acquire))
+            .min_by_key(|c| std::cmp::Reverse(head.wrapping_sub(*c)))
+            .unwrap_or(head);
+        self.gate.store(gate, Ordering::Release);
+        gate
+    }
+
+    /// 通过共享引用写入数据，最慢的订阅者还没有读完时通过 `TrySendError::Full` 将值原样返回
+    ///
+    /// # Safety
+    /// 同一时刻只能有一个线程调用此方法
+    pub(crate) unsafe fn push_unchecked(&self, value: T) -> Result<(), TrySendError<T>> {
+        let head = self.idx_head.load(Ordering::Relaxed);
+        // 和 subscribe_at 中 gate 的写入以及 head 的读取构成 SeqCst 的全序
+        if head.wrapping_sub(self.gate.load(Ordering::SeqCst)) >= SIZE
+            && head.wrapping_sub(self.update_gate(head)) >= SIZE
+        {
+            return Err(TrySendError::Full(value));
+        }
+        *self.m_data[Self::ring_idx(head)].get() = Some(value);
+        self.idx_head.store(head.wrapping_add(1), Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 订阅者按自己的游标读取数据，读取之后游标前进
+    fn read(&self, cursor: &AtomicUsize) -> Result<T, Error>
+    where
+        T: Clone,
+    {
+        let cur = cursor.load(Ordering::Relaxed);
+        if cur == self.idx_head.load(Ordering::Acquire) {
+            return Err(Error::Empty);
+        }
+        // 游标还没有越过这个槽位，写入者不会覆盖它
+        let res = unsafe { (*self.m_data[Self::ring_idx(cur)].get()).clone() };
+        cursor.store(cur.wrapping_add(1), Ordering::Release);
+        Ok(res.expect("published slot is always written"))
+    }
+
+    /// 读取数据，写入者已经被释放并且数据已经读完时返回 `Error::Disconnected`
+    fn read_disconnected(&self, cursor: &AtomicUsize) -> Result<T, Error>
+    where
+        T: Clone,
+    {
+        match self.read(cursor) {
+            Err(Error::Empty) if !self.tx_alive.load(Ordering::Acquire) => {
+                // 写入者在释放之前写入的数据对这里可见，需要再检查一次
+                match self.read(cursor) {
+                    Err(Error::Empty) => Err(Error::Disconnected),
+                    res => res,
+                }
+            }
+            res => res,
+        }
+    }
+
+    /// 注册新的游标，start 根据 head 和仍然保留的最早的数据选择起点，
+    /// 起点早于 gate 时降低 gate，持有锁期间写入者不会重新计算 gate
+    fn subscribe_at(self: &Arc<Self>, start: impl Fn(usize, usize) -> usize) -> RingBufferReceiver<T, SIZE> {
+        let mut cursors = self.lock_cursors();
+        let gate = self.gate.load(Ordering::Relaxed);
+        let mut head = self.idx_head.load(Ordering::SeqCst);
+        let start = loop {
+            let oldest = Self::oldest(head, gate);
+            // 起点必须落在 [oldest, head] 之间
+            let start = start(head, oldest);
+            let start = if head.wrapping_sub(start) > head.wrapping_sub(oldest) { oldest } else { start };
+            if head.wrapping_sub(start) <= head.wrapping_sub(gate) {
+                break start;
+            }
+            self.gate.store(start, Ordering::SeqCst);
+            // head 没有变化说明写入者还没有发布下一个数据，之后它会看到降低后的 gate
+            let cur = self.idx_head.load(Ordering::SeqCst);
+            if cur == head {
+                break start;
+            }
+            head = cur;
+        };
+        let cursor = Arc::new(CachePadded::new(AtomicUsize::new(start)));
+        cursors.push(cursor.clone());
+        RingBufferReceiver {
+            inner: self.clone(),
+            cursor,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        SIZE
+    }
+}
+
+/// 广播的写入者，没有实现 Clone，所以只有一个写入者
+pub struct RingBufferSender<T, const SIZE: usize> {
+    inner: Arc<RingBuffer<T, SIZE>>,
+}
+
+impl<T, const SIZE: usize> RingBufferSender<T, SIZE> {
+    /// 最多可保留的元素个数
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    /// 当前订阅者个数
+    #[inline]
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.lock_cursors().len()
+    }
+
+    /// 新增一个订阅者，从下一个写入的数据开始读取
+    pub fn subscribe(&self) -> RingBufferReceiver<T, SIZE> {
+        self.inner.subscribe_at(|head, _| head)
+    }
+
+    /// 新增一个订阅者，从仍然保留的最早的数据开始读取
+    pub fn subscribe_from_oldest(&self) -> RingBufferReceiver<T, SIZE> {
+        self.inner.subscribe_at(|_, oldest| oldest)
+    }
+
+    /// 写入数据，不会阻塞，最慢的订阅者还没有读完最早的数据时将值原样返回
+    /// 没有订阅者时写入总是成功，最早的数据会被覆盖
+    #[inline]
+    pub fn try_push(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        // Sender 没有实现 Clone，且写入需要 &mut self，所以同一时刻只会有一个写入者
+        let res = unsafe { self.inner.push_unchecked(value) };
+        if res.is_ok() {
+            self.inner.rx_waiters.notify();
+        }
+        res
+    }
+
+    /// 同 `try_push`
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        self.try_push(value)
+    }
+
+    /// 写入数据，最慢的订阅者还没有读完最早的数据时挂起当前线程
+    pub fn send(&mut self, value: T) -> Result<(), SendError<T>> {
+        match self.send_until(value, None) {
+            Ok(()) => Ok(()),
+            Err(SendTimeoutError::Disconnected(value)) => Err(SendError::Disconnected(value)),
+            Err(SendTimeoutError::Closed(value)) => Err(SendError::Closed(value)),
+            Err(SendTimeoutError::Timeout(_)) => unreachable!("sending without deadline never times out"),
+        }
+    }
+
+    /// 写入数据，最多等待 timeout，超时将值原样返回
+    #[inline]
+    pub fn send_timeout(&mut self, value: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.send_until(value, Instant::now().checked_add(timeout))
+    }
+
+    /// 写入数据，最多等待到 deadline，超时将值原样返回
+    #[inline]
+    pub fn send_deadline(&mut self, value: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        self.send_until(value, Some(deadline))
+    }
+
+    fn send_until(&mut self, value: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let inner = &self.inner;
+        let mut value = Some(value);
+        let res = signal::wait(&inner.tx_signal, deadline, || {
+            match unsafe { inner.push_unchecked(value.take()?) } {
+                Ok(()) => Some(Ok(())),
+                Err(TrySendError::Full(v)) => {
+                    value = Some(v);
+                    None
+                }
+                Err(TrySendError::Disconnected(v)) => Some(Err(SendTimeoutError::Disconnected(v))),
+                Err(TrySendError::Closed(v)) => Some(Err(SendTimeoutError::Closed(v))),
+            }
+        });
+        match (res, value) {
+            (Some(res), _) => {
+                if res.is_ok() {
+                    inner.rx_waiters.notify();
+                }
+                res
+            }
+            (None, Some(value)) => Err(SendTimeoutError::Timeout(value)),
+            (None, None) => unreachable!("value is kept until it is sent"),
+        }
+    }
+}
+
+impl<T, const SIZE: usize> Drop for RingBufferSender<T, SIZE> {
+    fn drop(&mut self) {
+        self.inner.tx_alive.store(false, Ordering::Release);
+        self.inner.rx_waiters.notify();
+    }
+}
+
+/// 广播的订阅者，每个订阅者独立读取全部数据
+/// Clone 得到的新订阅者从当前订阅者的位置开始读取
+pub struct RingBufferReceiver<T, const SIZE: usize> {
+    inner: Arc<RingBuffer<T, SIZE>>,
+    cursor: Cursor,
+}
+
+impl<T: Clone, const SIZE: usize> RingBufferReceiver<T, SIZE> {
+    /// 当前订阅者还没有读取的元素个数
+    #[inline]
+    pub fn len(&self) -> usize {
+        let cur = self.cursor.load(Ordering::Relaxed);
+        self.inner.idx_head.load(Ordering::Acquire).wrapping_sub(cur)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    /// 写入者是否已经被释放，被释放之后剩余的数据仍然可以读取
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        !self.inner.tx_alive.load(Ordering::Acquire)
+    }
+
+    /// 读取数据，不会阻塞，没有新数据时返回 `Error::Empty`，
+    /// 写入者已经被释放并且数据已经读完时返回 `Error::Disconnected`
+    #[inline]
+    pub fn try_pop(&mut self) -> Result<T, Error> {
+        let res = self.inner.read_disconnected(&self.cursor);
+        if !matches!(res, Err(Error::Empty | Error::Disconnected)) {
+            self.inner.tx_signal.notify();
+        }
+        res
+    }
+
+    /// 同 `try_pop`
+    #[inline]
+    pub fn pop(&mut self) -> Result<T, Error> {
+        self.try_pop()
+    }
+
+    /// 读取数据，没有新数据时挂起当前线程，直到写入者写入新的数据
+    #[inline]
+    pub fn recv(&mut self) -> Result<T, Error> {
+        self.recv_until(None)
+    }
+
+    /// 读取数据，没有新数据时最多等待 timeout，超时返回 `Error::Timeout`
+    #[inline]
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, Error> {
+        self.recv_until(Instant::now().checked_add(timeout))
+    }
+
+    /// 读取数据，没有新数据时最多等待到 deadline，超时返回 `Error::Timeout`
+    #[inline]
+    pub fn recv_deadline(&mut self, deadline: Instant) -> Result<T, Error> {
+        self.recv_until(Some(deadline))
+    }
+
+    fn recv_until(&mut self, deadline: Option<Instant>) -> Result<T, Error> {
+        let (inner, cursor) = (&self.inner, &self.cursor);
+        let res = signal::wait(&inner.rx_waiters, deadline, || {
+            match inner.read_disconnected(cursor) {
+                Err(Error::Empty) => None,
+                res => Some(res),
+            }
+        }).unwrap_or(Err(Error::Timeout));
+        if !matches!(res, Err(Error::Timeout | Error::Disconnected)) {
+            inner.tx_signal.notify();
+        }
+        res
+    }
+
+    /// 异步读取数据，没有新数据时登记当前任务的 Waker，写入者写入新的数据后唤醒
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, Error>> {
+        let (inner, cursor) = (&self.inner, &self.cursor);
+        let res = std::task::ready!(signal::poll(&inner.rx_waiters, cx, || {
+            match inner.read_disconnected(cursor) {
+                Err(Error::Empty) => None,
+                res => Some(res),
+            }
+        }));
+        if !matches!(res, Err(Error::Disconnected)) {
+            inner.tx_signal.notify();
+        }
+        Poll::Ready(res)
+    }
+
+    /// 异步读取数据，没有新数据时等待写入者写入新的数据
+    pub async fn recv_async(&mut self) -> Result<T, Error> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+}
+
+impl<T, const SIZE: usize> Clone for RingBufferReceiver<T, SIZE> {
+    fn clone(&self) -> Self {
+        // 当前游标上的数据还没有被读取，写入者不会覆盖，所以可以直接作为新订阅者的起点
+        let cur = self.cursor.load(Ordering::Relaxed);
+        self.inner.subscribe_at(|_, _| cur)
+    }
+}
+
+impl<T, const SIZE: usize> Drop for RingBufferReceiver<T, SIZE> {
+    fn drop(&mut self) {
+        self.inner.lock_cursors().retain(|c| !Arc::ptr_eq(c, &self.cursor));
+        self.inner.tx_signal.notify();
+    }
+}
+
+/// 创建广播环形缓冲区，返回写入者和一个从头开始读取的订阅者
+pub fn ringbuffer<T, const SIZE: usize>() -> (RingBufferSender<T, SIZE>, RingBufferReceiver<T, SIZE>)
+{
+    let ring = RingBuffer::new_arc();
+    let receiver = ring.subscribe_at(|head, _| head);
+    let sender = RingBufferSender {
+        inner: ring,
+    };
+    (sender, receiver)
+}
@@ -0,0 +1,19 @@
+//! 容量在运行时指定的 单生产者-单消费者 环形缓冲区，和 `queue::RingBuffer` 是同一个实现，
+//! SIZE 为 `queue::DYNAMIC`，槽位保存在堆上
+
+use crate::error::CapacityError;
+use crate::queue::{self, DYNAMIC};
+
+pub type RingBuffer<T> = queue::RingBuffer<T, DYNAMIC>;
+
+pub type RingBufferSender<T> = queue::RingBufferSender<T, DYNAMIC>;
+
+pub type RingBufferReceiver<T> = queue::RingBufferReceiver<T, DYNAMIC>;
+
+/// 创建至少能容纳 capacity 个元素的环形缓冲区，缓冲区大小向上取整为2的幂，
+/// capacity 为0或者取整后溢出时返回 `CapacityError`
+#[inline]
+pub fn ringbuffer<T>(capacity: usize) -> Result<(RingBufferSender<T>, RingBufferReceiver<T>), CapacityError>
+{
+    queue::ringbuffer_with_capacity(capacity)
+}
@@ -0,0 +1,182 @@
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of_mut;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crossbeam_utils::CachePadded;
+use crate::error::{Error, TrySendError};
+use crate::in_place::{self, init_array};
+
+/// 带序号的槽位
+/// stamp 等于写入位置时表示槽位空闲，可以写入；等于写入位置加一时表示已经写入，可以读取；
+/// 读取之后 stamp 增加 SIZE，留给下一圈的写入者。
+#[derive(Debug)]
+struct Slot<T> {
+    stamp: AtomicUsize,
+    /// 只有写入之后、读取之前是已初始化的
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// 多生产者-多消费者 有界队列
+/// 写入者之间通过 CAS 竞争 idx_head，读取者之间通过 CAS 竞争 idx_tail，
+/// 写入者和读取者之间通过槽位的 stamp 交接，所以可以放满全部 SIZE 个元素。
+/// 所有方法都只需要共享引用，多个线程共享时用 Arc 包裹即可。
+#[derive(Debug)]
+pub struct RingBuffer<T, const SIZE: usize = 4> {
+    m_data: [Slot<T>; SIZE],
+    idx_head: CachePadded<AtomicUsize>,
+    idx_tail: CachePadded<AtomicUsize>,
+}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    /// SIZE 必须是2的幂并且至少为2，否则无法通过编译：
+    /// 只有一个槽位时，写满之后的 stamp 等于下一圈的写入位置，队列会被当成空闲而覆盖尚未读取的数据
+    /// ```compile_fail
+    /// let queue = lockfree::lockfree_mpmc::RingBuffer::<u32, 1>::new();
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        unsafe { in_place::new_value(|ptr| Self::init(ptr)) }
+    }
+
+    /// 直接在 Arc 的堆内存中创建，SIZE 很大时避免在栈上构造整个槽位数组
+    pub fn new_arc() -> Arc<Self> {
+        unsafe { in_place::new_arc(|ptr| Self::init(ptr)) }
+    }
+
+    /// 逐个字段初始化 ptr 指向的内存
+    ///
+    /// # Safety
+    /// ptr 必须指向可写的未初始化内存
+    unsafe fn init(ptr: *mut Self) {
+        const { assert!(SIZE.is_power_of_two() && SIZE >= 2, "RingBuffer SIZE must be a power of two and at least 2") };
+        init_array(addr_of_mut!((*ptr).m_data), |i| Slot {
+            stamp: AtomicUsize::new(i),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        });
+        addr_of_mut!((*ptr).idx_head).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).idx_tail).write(CachePadded::new(AtomicUsize::new(0)));
+    }
+}
+
+impl<T, const SIZE: usize> Drop for RingBuffer<T, SIZE> {
+    fn drop(&mut self) {
+        // 没有其它线程访问，idx_tail到idx_head之间的槽位都已经写完
+        let tail = *self.idx_tail.get_mut();
+        let head = *self.idx_head.get_mut();
+        for i in 0..head.wrapping_sub(tail) {
+            let slot = &mut self.m_data[Self::ring_idx(tail.wrapping_add(i))];
+            unsafe { slot.value.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+impl<T, const SIZE: usize> Default for RingBuffer<T, SIZE> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 槽位的访问权由 CAS 和 stamp 的 Acquire/Release 交接，同一时刻只有一个线程访问某个槽位的数据
+unsafe impl<T: Send, const SIZE: usize> Sync for RingBuffer<T, SIZE> {}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    #[inline]
+    fn ring_idx(cur: usize) -> usize {
+        cur & (SIZE - 1)
+    }
+
+    /// 写入数据，可以被多个线程同时调用，队列已满时通过 `TrySendError::Full` 将值原样返回
+    pub fn push(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut head = self.idx_head.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.m_data[Self::ring_idx(head)];
+            let stamp = slot.stamp.load(Ordering::Acquire);
+            let diff = stamp.wrapping_sub(head) as isize;
+            if diff == 0 {
+                match self.idx_head.compare_exchange_weak(head, head.wrapping_add(1), Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        // 占用成功，上一圈的读取者已经取走数据，当前只有这一个写入者能访问它
+                        unsafe {
+                            (*slot.value.get()).write(value);
+                        }
+                        slot.stamp.store(head.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(cur) => head = cur,
+                }
+            } else if diff < 0 {
+                // 槽位还是上一圈的数据，队列已满
+                return Err(TrySendError::Full(value));
+            } else {
+                head = self.idx_head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// 取出数据，可以被多个线程同时调用，队列为空时返回 `Error::Empty`
+    pub fn pop(&self) -> Result<T, Error> {
+        let mut tail = self.idx_tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.m_data[Self::ring_idx(tail)];
+            let stamp = slot.stamp.load(Ordering::Acquire);
+            let diff = stamp.wrapping_sub(tail.wrapping_add(1)) as isize;
+            if diff == 0 {
+                match self.idx_tail.compare_exchange_weak(tail, tail.wrapping_add(1), Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        // 占用成功，写入者已经写完数据，当前只有这一个读取者能访问它
+                        let res = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.stamp.store(tail.wrapping_add(SIZE), Ordering::Release);
+                        return Ok(res);
+                    }
+                    Err(cur) => tail = cur,
+                }
+            } else if diff < 0 {
+                // 槽位还没有被写入，队列为空
+                return Err(Error::Empty);
+            } else {
+                tail = self.idx_tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == SIZE
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前队列中的元素个数，并发修改时只是一个近似值
+    pub fn len(&self) -> usize {
+        loop {
+            let idx_tail = self.idx_tail.load(Ordering::SeqCst);
+            let idx_head = self.idx_head.load(Ordering::SeqCst);
+            // 两次读取之间 idx_tail 没有变化，说明得到的是一致的快照
+            if self.idx_tail.load(Ordering::SeqCst) == idx_tail {
+                return idx_head.wrapping_sub(idx_tail).min(SIZE);
+            }
+        }
+    }
+
+    /// 最多可容纳的元素个数
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    /// 剩余可写入的元素个数
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        SIZE
+    }
+}
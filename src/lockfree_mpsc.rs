@@ -0,0 +1,333 @@
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of_mut;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::task::Waker;
+use crossbeam_utils::CachePadded;
+use crate::error::{Error, TrySendError};
+use crate::handle::{MultiSender, Queue, Receiver, Sender};
+use crate::signal::{Signal, WaitList};
+use crate::in_place::{self, init_array};
+use crate::storage::Uninit;
+
+/// 空槽，或者写入者已经占用但还没写完
+const EMPTY: u8 = 0;
+/// 写入者已经写完数据
+const READY: u8 = 1;
+/// 写入者放弃了占用的槽位，读取者直接跳过
+const SKIP: u8 = 2;
+
+/// 槽位，state 标记写入者是否已经写完数据
+#[derive(Debug)]
+struct Slot<T> {
+    state: AtomicU8,
+    /// 只有 state 为 READY 时是已初始化的
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// 多生产者-单消费者 环形缓冲区
+/// 写入者之间通过 CAS 竞争 idx_head 占用槽位，写完之后把槽位标记为 READY，
+/// 读取者只看 idx_tail 指向槽位的标记，所以写入者占用槽位但还没写完时，读取者会认为队列为空。
+/// 写入者通过 `reserve` 占用槽位之后放弃写入时，槽位标记为 SKIP，读取者遇到时直接跳过。
+/// 索引单调递增，访问槽位时再取模，避免多个写入者 CAS 时出现 ABA 问题。
+/// 容量与 `queue::RingBuffer` 一致，为 SIZE。
+#[derive(Debug)]
+pub struct RingBuffer<T, const SIZE: usize = 4> {
+    m_data: [Slot<T>; SIZE],
+    idx_head: CachePadded<AtomicUsize>,
+    idx_tail: CachePadded<AtomicUsize>,
+    /// 写入者等待空位
+    tx_waiters: WaitList,
+    /// 读取者等待数据
+    rx_signal: Signal,
+    /// 没有关闭的写入者个数
+    tx_count: AtomicUsize,
+    /// 读取者是否存活
+    rx_alive: AtomicBool,
+}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    /// 直接在 Arc 的堆内存中创建，槽位数组不经过栈
+    fn new_arc() -> Arc<Self> {
+        unsafe { in_place::new_arc(|ptr| Self::init(ptr)) }
+    }
+
+    /// 逐个字段初始化 ptr 指向的内存
+    ///
+    /// # Safety
+    /// ptr 必须指向可写的未初始化内存
+    unsafe fn init(ptr: *mut Self) {
+        const { assert!(SIZE.is_power_of_two(), "RingBuffer SIZE must be a power of two") };
+        init_array(addr_of_mut!((*ptr).m_data), |_| Slot {
+            state: AtomicU8::new(EMPTY),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        });
+        addr_of_mut!((*ptr).idx_head).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).idx_tail).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).tx_waiters).write(WaitList::new());
+        addr_of_mut!((*ptr).rx_signal).write(Signal::new());
+        addr_of_mut!((*ptr).tx_count).write(AtomicUsize::new(1));
+        addr_of_mut!((*ptr).rx_alive).write(AtomicBool::new(true));
+    }
+}
+
+impl<T, const SIZE: usize> Drop for RingBuffer<T, SIZE> {
+    fn drop(&mut self) {
+        // 所有句柄都已经被释放，标记为 READY 的槽位就是还没取出的数据
+        for slot in self.m_data.iter_mut() {
+            if *slot.state.get_mut() == READY {
+                unsafe { slot.value.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+/// 写入者通过 CAS 独占槽位，读取者通过 state 的 Acquire/Release 获取槽位，
+/// 通过共享引用能调用的安全方法只读取原子索引
+unsafe impl<T: Send, const SIZE: usize> Sync for RingBuffer<T, SIZE> {}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    #[inline]
+    fn ring_idx(cur: usize) -> usize {
+        cur & (SIZE - 1)
+    }
+
+    #[inline]
+    fn slot(&self, cur: usize) -> &Slot<T> {
+        &self.m_data[Self::ring_idx(cur)]
+    }
+
+    /// 写入数据，队列已满时通过 `TrySendError::Full` 将值原样返回
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        unsafe { Queue::push_unchecked(self, value) }.map_err(TrySendError::Full)
+    }
+
+    /// 取出数据
+    #[inline]
+    pub fn pop(&mut self) -> Result<T, Error> {
+        unsafe { Queue::pop_unchecked(self) }.ok_or(Error::Empty)
+    }
+
+    /// 跳过被放弃的槽位，返回idx_tail指向的已写完的槽位，队列为空或者写入者还没写完时返回 None
+    ///
+    /// # Safety
+    /// 只能由唯一的读取者调用
+    unsafe fn ready_tail(&self) -> Option<usize> {
+        let mut skipped = false;
+        let res = loop {
+            let tail = self.idx_tail.load(Ordering::Relaxed);
+            let slot = self.slot(tail);
+            match slot.state.load(Ordering::Acquire) {
+                READY => break Some(tail),
+                SKIP => {
+                    slot.state.store(EMPTY, Ordering::Relaxed);
+                    self.idx_tail.store(tail.wrapping_add(1), Ordering::Release);
+                    skipped = true;
+                }
+                _ => break None,
+            }
+        };
+        if skipped {
+            // 跳过的槽位也腾出了空位
+            self.tx_waiters.notify();
+        }
+        res
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() >= SIZE
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前队列中的元素个数，包括已经被写入者占用但还没写完或者被放弃的槽位
+    #[inline]
+    pub fn len(&self) -> usize {
+        let idx_tail = self.idx_tail.load(Ordering::Acquire);
+        let idx_head = self.idx_head.load(Ordering::Acquire);
+        idx_head.wrapping_sub(idx_tail).min(SIZE)
+    }
+
+    /// 最多可容纳的元素个数，与 `queue::RingBuffer` 保持一致
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    /// 剩余可写入的元素个数
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        SIZE
+    }
+}
+
+/// 写入一侧的方法通过 CAS 占用槽位，可以被多个写入者同时调用，读取一侧只有一个读取者
+unsafe impl<T, const SIZE: usize> Queue for RingBuffer<T, SIZE> {
+    type Item = T;
+    type Storage = Uninit;
+
+    #[inline]
+    fn len(&self) -> usize {
+        RingBuffer::len(self)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    #[inline]
+    fn has_senders(&self) -> bool {
+        self.tx_count.load(Ordering::Acquire) != 0
+    }
+
+    #[inline]
+    fn has_receivers(&self) -> bool {
+        self.rx_alive.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    fn release_sender(&self) {
+        self.tx_count.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    #[inline]
+    fn release_receiver(&self) {
+        self.rx_alive.store(false, Ordering::Release);
+    }
+
+    #[inline]
+    fn register_tx(&self, waker: &Waker) {
+        self.tx_waiters.register(waker);
+    }
+
+    #[inline]
+    fn notify_tx(&self) {
+        self.tx_waiters.notify();
+    }
+
+    #[inline]
+    fn register_rx(&self, waker: &Waker) {
+        self.rx_signal.register(waker);
+    }
+
+    #[inline]
+    fn notify_rx(&self) {
+        self.rx_signal.notify();
+    }
+
+    unsafe fn push_unchecked(&self, value: T) -> Result<(), T> {
+        match self.reserve_unchecked() {
+            Some(head) => {
+                (*self.slot(head).value.get()).write(value);
+                self.commit_unchecked(head);
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+
+    /// 通过 CAS 推进idx_head占用槽位，可以被多个写入者同时调用
+    unsafe fn reserve_unchecked(&self) -> Option<usize> {
+        let mut head = self.idx_head.load(Ordering::Relaxed);
+        loop {
+            let tail = self.idx_tail.load(Ordering::Acquire);
+            if head.wrapping_sub(tail) >= SIZE {
+                return None;
+            }
+            match self.idx_head.compare_exchange_weak(head, head.wrapping_add(1), Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => return Some(head),
+                Err(cur) => head = cur,
+            }
+        }
+    }
+
+    /// 占用成功说明读取者已经取走了这个槽位之前的数据，当前只有这一个写入者能访问它
+    #[inline]
+    unsafe fn slot_unchecked(&self, head: usize) -> &mut MaybeUninit<T> {
+        &mut *self.slot(head).value.get()
+    }
+
+    #[inline]
+    unsafe fn commit_unchecked(&self, head: usize) {
+        self.slot(head).state.store(READY, Ordering::Release);
+    }
+
+    /// idx_head 已经越过了这个槽位，只能留下 SKIP 标记让读取者跳过
+    #[inline]
+    unsafe fn abort_unchecked(&self, head: usize) {
+        self.slot(head).state.store(SKIP, Ordering::Release);
+    }
+
+    #[inline]
+    unsafe fn pop_unchecked(&self) -> Option<T> {
+        let tail = self.ready_tail()?;
+        Some(self.consume_unchecked(tail))
+    }
+
+    /// 只有一个读取者，写入者在idx_tail越过槽位之前不会复用它，不需要额外的标记
+    #[inline]
+    unsafe fn claim_unchecked(&self) -> Option<usize> {
+        self.ready_tail()
+    }
+
+    #[inline]
+    unsafe fn claimed_unchecked(&self, tail: usize) -> &mut T {
+        (*self.slot(tail).value.get()).assume_init_mut()
+    }
+
+    unsafe fn consume_unchecked(&self, tail: usize) -> T {
+        let slot = self.slot(tail);
+        let res = (*slot.value.get()).assume_init_read();
+        slot.state.store(EMPTY, Ordering::Relaxed);
+        self.idx_tail.store(tail.wrapping_add(1), Ordering::Release);
+        res
+    }
+
+    #[inline]
+    unsafe fn release_unchecked(&self, _tail: usize) {}
+}
+
+unsafe impl<T, const SIZE: usize> MultiSender for RingBuffer<T, SIZE> {
+    #[inline]
+    fn acquire_sender(&self) {
+        self.tx_count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 这里采用Reader 和Writer的分离实现
+/// Writer实现了Clone，可以有多个写入者
+/// 由于Reader没有实现Clone，所以Reader不能共享所有权
+/// 因此，就实现了 多生产者-单消费者 模式
+pub type RingBufferSender<T, const SIZE: usize> = Sender<RingBuffer<T, SIZE>>;
+
+pub type RingBufferReceiver<T, const SIZE: usize> = Receiver<RingBuffer<T, SIZE>>;
+
+/// 创建 多生产者-单消费者 环形缓冲区，接口和 `queue::ringbuffer` 相同，写入者可以 Clone
+/// ```
+/// let (mut tx, mut rx) = lockfree::lockfree_mpsc::ringbuffer::<u32, 4>();
+/// let mut tx2 = tx.clone();
+/// tx.push(1).unwrap();
+/// tx2.push(2).unwrap();
+/// tx.close();
+/// assert_eq!(rx.pop().unwrap(), 1);
+/// drop(tx2);
+/// assert_eq!(rx.pop().unwrap(), 2);
+/// assert!(rx.pop().is_err());
+/// ```
+pub fn ringbuffer<T, const SIZE: usize>() -> (RingBufferSender<T, SIZE>, RingBufferReceiver<T, SIZE>)
+{
+    let ring = RingBuffer::new_arc();
+    (Sender::new(ring.clone()), Receiver::new(ring))
+}
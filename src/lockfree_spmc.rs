@@ -0,0 +1,337 @@
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of_mut;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::Waker;
+use crossbeam_utils::CachePadded;
+use crate::error::{Error, TrySendError};
+use crate::handle::{MultiReceiver, Queue, Receiver, Sender};
+use crate::signal::{Signal, WaitList};
+use crate::in_place::{self, init_array};
+use crate::storage::Uninit;
+
+/// 读取者占用了最早的数据，此时其他读取者不能取走它
+const PINNED: usize = 1 << (usize::BITS - 1);
+/// 去掉标记位之后的索引
+const IDX_MASK: usize = !PINNED;
+
+/// 槽位，ready 标记数据是否还没有被读取者取走
+#[derive(Debug)]
+struct Slot<T> {
+    ready: AtomicBool,
+    /// 只有 ready 为 true 时是已初始化的
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// 单生产者-多消费者 环形缓冲区，每个数据只会被其中一个读取者取走
+/// 读取者之间通过 CAS 竞争 idx_tail 占用槽位，取走数据之后清除槽位的 ready，
+/// 写入者除了检查 idx_tail 之外，还要等待槽位的 ready 被清除之后才能复用该槽位。
+/// `Peek` 和 `ReadGuard` 需要长时间占用最早的数据，改为在idx_tail上标记PINNED，标记期间其他读取者认为队列为空。
+/// 索引单调递增，访问槽位时再取模，避免多个读取者 CAS 时出现 ABA 问题。
+/// 容量与 `queue::RingBuffer` 一致，为 SIZE。
+#[derive(Debug)]
+pub struct RingBuffer<T, const SIZE: usize = 4> {
+    m_data: [Slot<T>; SIZE],
+    idx_head: CachePadded<AtomicUsize>,
+    idx_tail: CachePadded<AtomicUsize>,
+    /// 写入者等待空位
+    tx_signal: Signal,
+    /// 读取者等待数据
+    rx_waiters: WaitList,
+    /// 写入者是否存活
+    tx_alive: AtomicBool,
+    /// 存活的读取者个数
+    rx_count: AtomicUsize,
+}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    /// 直接在 Arc 的堆内存中创建，槽位数组不经过栈
+    fn new_arc() -> Arc<Self> {
+        unsafe { in_place::new_arc(|ptr| Self::init(ptr)) }
+    }
+
+    /// 逐个字段初始化 ptr 指向的内存
+    ///
+    /// # Safety
+    /// ptr 必须指向可写的未初始化内存
+    unsafe fn init(ptr: *mut Self) {
+        const { assert!(SIZE.is_power_of_two(), "RingBuffer SIZE must be a power of two") };
+        init_array(addr_of_mut!((*ptr).m_data), |_| Slot {
+            ready: AtomicBool::new(false),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        });
+        addr_of_mut!((*ptr).idx_head).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).idx_tail).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).tx_signal).write(Signal::new());
+        addr_of_mut!((*ptr).rx_waiters).write(WaitList::new());
+        addr_of_mut!((*ptr).tx_alive).write(AtomicBool::new(true));
+        addr_of_mut!((*ptr).rx_count).write(AtomicUsize::new(1));
+    }
+}
+
+impl<T, const SIZE: usize> Drop for RingBuffer<T, SIZE> {
+    fn drop(&mut self) {
+        // 所有句柄都已经被释放，ready 为 true 的槽位就是还没取出的数据
+        for slot in self.m_data.iter_mut() {
+            if *slot.ready.get_mut() {
+                unsafe { slot.value.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+/// 读取者通过 CAS 独占槽位，写入者通过 ready 的 Acquire/Release 确认槽位已经空闲，
+/// 通过共享引用能调用的安全方法只读取原子索引
+unsafe impl<T: Send, const SIZE: usize> Sync for RingBuffer<T, SIZE> {}
+
+impl<T, const SIZE: usize> RingBuffer<T, SIZE> {
+    #[inline]
+    fn ring_idx(cur: usize) -> usize {
+        cur & (SIZE - 1)
+    }
+
+    #[inline]
+    fn next_idx(cur: usize) -> usize {
+        cur.wrapping_add(1) & IDX_MASK
+    }
+
+    #[inline]
+    fn distance(tail: usize, head: usize) -> usize {
+        head.wrapping_sub(tail) & IDX_MASK
+    }
+
+    #[inline]
+    fn slot(&self, cur: usize) -> &Slot<T> {
+        &self.m_data[Self::ring_idx(cur)]
+    }
+
+    /// 写入数据，队列已满时通过 `TrySendError::Full` 将值原样返回
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        unsafe { Queue::push_unchecked(self, value) }.map_err(TrySendError::Full)
+    }
+
+    /// 取出数据
+    #[inline]
+    pub fn pop(&mut self) -> Result<T, Error> {
+        unsafe { Queue::pop_unchecked(self) }.ok_or(Error::Empty)
+    }
+
+    /// 通过 CAS 把idx_tail从 tail 改为 new，队列为空或者最早的数据已经被占用时返回 None
+    fn advance_tail(&self, new: impl Fn(usize) -> usize) -> Option<usize> {
+        let mut tail = self.idx_tail.load(Ordering::Relaxed);
+        loop {
+            if tail & PINNED != 0 {
+                return None;
+            }
+            let head = self.idx_head.load(Ordering::Acquire);
+            if tail == head {
+                return None;
+            }
+            match self.idx_tail.compare_exchange_weak(tail, new(tail), Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => return Some(tail),
+                Err(cur) => tail = cur,
+            }
+        }
+    }
+
+    /// 写入者下一个要写的槽位是否还没有被读取者取完
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        let head = self.idx_head.load(Ordering::Acquire);
+        self.len() >= SIZE || self.slot(head).ready.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前队列中还没有被读取者取走的元素个数，包括被 `Peek` 或 `ReadGuard` 占用的数据
+    #[inline]
+    pub fn len(&self) -> usize {
+        let idx_tail = self.idx_tail.load(Ordering::Acquire) & IDX_MASK;
+        let idx_head = self.idx_head.load(Ordering::Acquire);
+        Self::distance(idx_tail, idx_head).min(SIZE)
+    }
+
+    /// 最多可容纳的元素个数，与 `queue::RingBuffer` 保持一致
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    /// 剩余可写入的元素个数
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        SIZE
+    }
+}
+
+/// 读取一侧的方法通过 CAS 占用槽位，可以被多个读取者同时调用，写入一侧只有一个写入者
+unsafe impl<T, const SIZE: usize> Queue for RingBuffer<T, SIZE> {
+    type Item = T;
+    type Storage = Uninit;
+
+    #[inline]
+    fn len(&self) -> usize {
+        RingBuffer::len(self)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        RingBuffer::is_full(self)
+    }
+
+    #[inline]
+    fn has_senders(&self) -> bool {
+        self.tx_alive.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    fn has_receivers(&self) -> bool {
+        self.rx_count.load(Ordering::Acquire) != 0
+    }
+
+    #[inline]
+    fn release_sender(&self) {
+        self.tx_alive.store(false, Ordering::Release);
+    }
+
+    #[inline]
+    fn release_receiver(&self) {
+        self.rx_count.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    #[inline]
+    fn register_tx(&self, waker: &Waker) {
+        self.tx_signal.register(waker);
+    }
+
+    #[inline]
+    fn notify_tx(&self) {
+        self.tx_signal.notify();
+    }
+
+    #[inline]
+    fn register_rx(&self, waker: &Waker) {
+        self.rx_waiters.register(waker);
+    }
+
+    #[inline]
+    fn notify_rx(&self) {
+        self.rx_waiters.notify();
+    }
+
+    unsafe fn push_unchecked(&self, value: T) -> Result<(), T> {
+        match self.reserve_unchecked() {
+            Some(head) => {
+                (*self.slot(head).value.get()).write(value);
+                self.commit_unchecked(head);
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+
+    unsafe fn reserve_unchecked(&self) -> Option<usize> {
+        let head = self.idx_head.load(Ordering::Relaxed);
+        let tail = self.idx_tail.load(Ordering::Acquire) & IDX_MASK;
+        // 读取者占用了槽位但还没取完数据时，槽位仍然不能复用
+        if Self::distance(tail, head) >= SIZE || self.slot(head).ready.load(Ordering::Acquire) {
+            return None;
+        }
+        Some(head)
+    }
+
+    #[inline]
+    unsafe fn slot_unchecked(&self, head: usize) -> &mut MaybeUninit<T> {
+        &mut *self.slot(head).value.get()
+    }
+
+    #[inline]
+    unsafe fn commit_unchecked(&self, head: usize) {
+        self.slot(head).ready.store(true, Ordering::Relaxed);
+        self.idx_head.store(Self::next_idx(head), Ordering::Release);
+    }
+
+    /// 通过 CAS 推进idx_tail占用槽位，可以被多个读取者同时调用
+    unsafe fn pop_unchecked(&self) -> Option<T> {
+        let tail = self.advance_tail(Self::next_idx)?;
+        let slot = self.slot(tail);
+        // 占用成功说明写入者已经写完这个槽位，并且在 ready 被清除之前不会复用它
+        let res = (*slot.value.get()).assume_init_read();
+        slot.ready.store(false, Ordering::Release);
+        Some(res)
+    }
+
+    /// 在idx_tail上标记PINNED，其他读取者在 consume 或 release 之前不会取走最早的数据
+    #[inline]
+    unsafe fn claim_unchecked(&self) -> Option<usize> {
+        self.advance_tail(|tail| tail | PINNED)
+    }
+
+    #[inline]
+    unsafe fn claimed_unchecked(&self, tail: usize) -> &mut T {
+        (*self.slot(tail).value.get()).assume_init_mut()
+    }
+
+    unsafe fn consume_unchecked(&self, tail: usize) -> T {
+        let slot = self.slot(tail);
+        let res = (*slot.value.get()).assume_init_read();
+        slot.ready.store(false, Ordering::Release);
+        self.idx_tail.store(Self::next_idx(tail), Ordering::Release);
+        // 等待PINNED清除的读取者可以继续读取
+        self.rx_waiters.notify();
+        res
+    }
+
+    #[inline]
+    unsafe fn release_unchecked(&self, tail: usize) {
+        self.idx_tail.store(tail, Ordering::Release);
+        self.rx_waiters.notify();
+    }
+}
+
+unsafe impl<T, const SIZE: usize> MultiReceiver for RingBuffer<T, SIZE> {
+    #[inline]
+    fn acquire_receiver(&self) {
+        self.rx_count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 这里采用Reader 和Writer的分离实现
+/// 由于Writer没有实现Clone，所以Writer不能共享所有权
+/// Reader实现了Clone，可以有多个读取者，每个数据只会被其中一个读取者取走
+/// 因此，就实现了 单生产者-多消费者 模式
+pub type RingBufferSender<T, const SIZE: usize> = Sender<RingBuffer<T, SIZE>>;
+
+pub type RingBufferReceiver<T, const SIZE: usize> = Receiver<RingBuffer<T, SIZE>>;
+
+/// 创建 单生产者-多消费者 环形缓冲区，接口和 `queue::ringbuffer` 相同，读取者可以 Clone
+/// ```
+/// let (mut tx, mut rx) = lockfree::lockfree_spmc::ringbuffer::<u32, 4>();
+/// let mut rx2 = rx.clone();
+/// tx.push(1).unwrap();
+/// tx.push(2).unwrap();
+/// tx.close();
+/// assert_eq!(rx2.pop().unwrap(), 1);
+/// assert_eq!(rx.pop().unwrap(), 2);
+/// assert!(rx.pop().is_err());
+/// ```
+pub fn ringbuffer<T, const SIZE: usize>() -> (RingBufferSender<T, SIZE>, RingBufferReceiver<T, SIZE>)
+{
+    let ring = RingBuffer::new_arc();
+    (Sender::new(ring.clone()), Receiver::new(ring))
+}
@@ -0,0 +1,856 @@
+#![allow(dead_code)]
+
+use std::ptr::addr_of_mut;
+use std::sync::Arc;
+use std::task::Waker;
+use crossbeam_utils::{Backoff, CachePadded};
+use crate::signal::Signal;
+use crate::in_place::{self, init_array};
+use crate::error::{CapacityError, Error, TrySendError};
+use crate::handle::{self, Queue, Receiver, Sender};
+use crate::storage::{Storage, Uninit};
+use crate::sync::{self, AtomicBool, AtomicUsize, Ordering, UnsafeCell};
+
+/// 这里其实不需要限制RingBuffer，因为RingBuffer的实现都是符合借用规则的
+/// 所以不必担心安全问题，默认情况下只会有一个线程持有对象，因为没有提供Clone方法，即便用Arc指针
+/// 也无法通过不可变引用修改内部数据
+/// 如果想要修改内部数据就必须在包一层Mutex，这也是完全符合安全原则的
+/// 因此如果想要使用就必须使用unsafe，此时安全由使用者确保
+/// 所以在下面的读写分离实现中，使用了Arc共享，槽位通过UnsafeCell实现内部可变，
+/// 写入者只访问idx_head指向的空槽，读取者只访问idx_tail指向的已写入槽，两者不会重叠。
+/// idx_head和idx_tail只增不减，取模之后才是槽位下标，两者之差就是元素个数，所以SIZE个槽位都能存放数据。
+/// 覆盖模式下写入者在队列已满时通过CAS推进idx_tail丢弃最早的数据，读取者也要先通过CAS
+/// 在idx_tail上标记READING占用槽位再读取，两者通过同一个原子变量竞争，同一个槽位只会被一方访问。
+/// `Peek` 和 `ReadGuard` 需要长时间占用最早的数据，改为标记PINNED，此时队列已满的话写入者丢弃新写入的数据，
+/// 它们释放时清除标记，写入者恢复丢弃最早的数据。
+/// 由于值要被取走，转移所有权，槽位如何存放数据由存储策略 S 决定，只有idx_tail到idx_head之间的槽位是已写入的，
+/// 释放时只析构这些槽位。默认的 `Uninit` 使用MaybeUninit<T>，不需要 Option 标记，也不要求 T 实现 Default；
+/// `Uninit` 和 `Defaulted` 的槽位和 T 的内存布局相同，可以通过 `WriteChunk` 和 `ReadChunk` 按切片直接访问。
+/// SIZE 为 `DYNAMIC` 时缓冲区大小在创建时指定，槽位保存在堆上的 m_heap 中，否则保存在 m_data 中，
+/// 两者只有一个非空，由 SIZE 在编译期选出，读写协议完全相同。
+#[derive(Debug)]
+pub struct RingBuffer<T, const SIZE: usize = 4, S: Storage<T> = Uninit> {
+    m_data: [UnsafeCell<S::Slot>; SIZE],
+    /// 容量在运行时指定时的槽位，大小总是2的幂
+    m_heap: Box<[UnsafeCell<S::Slot>]>,
+    idx_head: CachePadded<AtomicUsize>,
+    idx_tail: CachePadded<AtomicUsize>,
+    /// 写入者等待空位
+    tx_signal: Signal,
+    /// 读取者等待数据
+    rx_signal: Signal,
+    /// 写入者是否存活
+    tx_alive: AtomicBool,
+    /// 读取者是否存活
+    rx_alive: AtomicBool,
+    /// 是否为覆盖模式，队列已满时写入者覆盖最早的数据而不是返回 Full
+    overwrite: bool,
+    /// 上一次取出数据之后被覆盖的元素个数
+    missed: AtomicUsize,
+    /// 最近一次取出的数据之前被覆盖的元素个数，只由读取者访问
+    rx_missed: UnsafeCell<usize>,
+}
+
+/// 读取者正在读取idx_tail指向的槽位，写入者不能覆盖它，需要等待读取完成
+const READING: usize = 1 << (usize::BITS - 1);
+/// 读取者通过 `Peek` 或 `ReadGuard` 占用了idx_tail指向的槽位，写入者不能覆盖它，也不等待
+const PINNED: usize = 1 << (usize::BITS - 2);
+const CLAIMED: usize = READING | PINNED;
+/// 索引只使用标记位以下的部分，在这个范围内回绕，缓冲区大小是2的幂所以回绕时槽位下标仍然连续
+const IDX_MASK: usize = !CLAIMED;
+
+/// SIZE 取这个值时缓冲区大小在运行时指定，槽位保存在堆上，见 `ringbuffer_with_capacity`
+pub const DYNAMIC: usize = 0;
+
+impl<T, const SIZE: usize, S: Storage<T>> RingBuffer<T, SIZE, S> {
+    /// 直接在 Arc 的堆内存中创建，槽位数组不经过栈
+    fn new_arc(overwrite: bool) -> Arc<Self> {
+        const { assert!(SIZE.is_power_of_two(), "RingBuffer SIZE must be a power of two") };
+        unsafe { in_place::new_arc(|ptr| Self::init(ptr, overwrite, Box::default())) }
+    }
+
+    /// 逐个字段初始化 ptr 指向的内存
+    ///
+    /// # Safety
+    /// ptr 必须指向可写的未初始化内存
+    unsafe fn init(ptr: *mut Self, overwrite: bool, heap: Box<[UnsafeCell<S::Slot>]>) {
+        addr_of_mut!((*ptr).idx_head).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).idx_tail).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).tx_signal).write(Signal::new());
+        addr_of_mut!((*ptr).rx_signal).write(Signal::new());
+        addr_of_mut!((*ptr).tx_alive).write(AtomicBool::new(true));
+        addr_of_mut!((*ptr).rx_alive).write(AtomicBool::new(true));
+        addr_of_mut!((*ptr).overwrite).write(overwrite);
+        addr_of_mut!((*ptr).missed).write(AtomicUsize::new(0));
+        addr_of_mut!((*ptr).rx_missed).write(UnsafeCell::new(0));
+        init_array(addr_of_mut!((*ptr).m_data), |_| UnsafeCell::new(S::vacant()));
+        addr_of_mut!((*ptr).m_heap).write(heap);
+    }
+}
+
+impl<T, S: Storage<T>> RingBuffer<T, DYNAMIC, S> {
+    /// 创建至少能容纳 capacity 个元素的缓冲区，缓冲区大小向上取整为2的幂，
+    /// capacity 为0或者取整后溢出时返回 `CapacityError`
+    fn with_capacity(capacity: usize, overwrite: bool) -> Result<Arc<Self>, CapacityError> {
+        let size = capacity
+            .checked_next_power_of_two()
+            .filter(|_| capacity > 0)
+            .ok_or(CapacityError(capacity))?;
+        let heap = (0..size).map(|_| UnsafeCell::new(S::vacant())).collect();
+        Ok(unsafe { in_place::new_arc(|ptr| Self::init(ptr, overwrite, heap)) })
+    }
+}
+
+impl<T, const SIZE: usize, S: Storage<T>> Drop for RingBuffer<T, SIZE, S> {
+    fn drop(&mut self) {
+        // 写入者和读取者都已经被释放，没有被占用的槽位，只有还没取出的数据需要析构
+        let tail = self.idx_tail.load(Ordering::Acquire) & !CLAIMED;
+        let head = self.idx_head.load(Ordering::Acquire);
+        for i in 0..Self::distance(tail, head) {
+            self.slot(tail.wrapping_add(i)).with_mut(|slot| unsafe { S::clear(&mut *slot) });
+        }
+    }
+}
+
+/// 槽位只会被唯一的写入者或唯一的读取者访问，访问权由idx_head和idx_tail的Acquire/Release交接，
+/// 通过共享引用能调用的安全方法只读取原子索引，rx_missed 只在读取者持有 &mut 时修改
+unsafe impl<T: Send, const SIZE: usize, S: Storage<T>> Sync for RingBuffer<T, SIZE, S> where S::Slot: Send {}
+
+impl<T, const SIZE: usize, S: Storage<T>> RingBuffer<T, SIZE, S> {
+    #[inline]
+    fn next_idx(cur: usize) -> usize {
+        cur.wrapping_add(1) & IDX_MASK
+    }
+
+    /// 从 tail 到 head 之间的元素个数
+    #[inline]
+    fn distance(tail: usize, head: usize) -> usize {
+        head.wrapping_sub(tail) & IDX_MASK
+    }
+
+    #[inline]
+    fn ring_idx(&self, cur: usize) -> usize {
+        cur & (self.capacity() - 1)
+    }
+
+    /// 全部槽位，SIZE 为 `DYNAMIC` 时在堆上
+    #[inline]
+    fn slots(&self) -> &[UnsafeCell<S::Slot>] {
+        if SIZE == DYNAMIC {
+            &self.m_heap
+        } else {
+            &self.m_data
+        }
+    }
+
+    /// 索引 cur 对应的槽位
+    #[inline]
+    fn slot(&self, cur: usize) -> &UnsafeCell<S::Slot> {
+        &self.slots()[self.ring_idx(cur)]
+    }
+
+    /// 写入数据，队列已满时通过 `TrySendError::Full` 将值原样返回
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        unsafe { Queue::push_unchecked(self, value) }.map_err(TrySendError::Full)
+    }
+
+    /// 取出数据
+    #[inline]
+    pub fn pop(&mut self) -> Result<T, Error> {
+        unsafe { Queue::pop_unchecked(self) }.ok_or(Error::Empty)
+    }
+
+    /// 覆盖模式下为 head 腾出空位，队列未满时什么也不做
+    /// 最早的数据被 `Peek` 或 `ReadGuard` 占用时无法腾出空位，返回 false
+    ///
+    /// # Safety
+    /// 只能由唯一的写入者调用
+    unsafe fn evict_oldest(&self, head: usize) -> bool {
+        let backoff = Backoff::new();
+        loop {
+            let tail = self.idx_tail.load(Ordering::Acquire);
+            if Self::distance(tail & !CLAIMED, head) < self.capacity() {
+                return true;
+            }
+            if tail & PINNED != 0 {
+                return false;
+            }
+            if tail & READING != 0 {
+                // 读取者正在取走最早的数据，取完之后就有空位
+                sync::snooze(&backoff);
+                continue;
+            }
+            if self.idx_tail.compare_exchange(tail, Self::next_idx(tail), Ordering::AcqRel, Ordering::Acquire).is_ok() {
+                // 抢在读取者之前推进了idx_tail，这个槽位现在只属于写入者
+                self.slot(tail).with_mut(|slot| S::clear(&mut *slot));
+                self.missed.fetch_add(1, Ordering::Release);
+                return true;
+            }
+        }
+    }
+
+    /// 占用idx_tail指向的槽位，返回槽位下标，队列为空时返回 None
+    /// 覆盖模式下在idx_tail上标记 mark（READING 或 PINNED），防止写入者同时丢弃它，已经占用时直接返回
+    ///
+    /// # Safety
+    /// 只能由唯一的读取者调用
+    unsafe fn claim(&self, mark: usize) -> Option<usize> {
+        let mut tail = self.idx_tail.load(Ordering::Acquire);
+        loop {
+            if tail & CLAIMED != 0 {
+                return Some(tail & !CLAIMED);
+            }
+            if self.idx_head.load(Ordering::Acquire) == tail {
+                return None;
+            }
+            if !self.overwrite {
+                return Some(tail);
+            }
+            match self.idx_tail.compare_exchange(tail, tail | mark, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Some(tail),
+                // 写入者丢弃了最早的数据，从新的位置开始
+                Err(cur) => tail = cur,
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let idx_tail = self.idx_tail.load(Ordering::Acquire) & !CLAIMED;
+        let idx_head = self.idx_head.load(Ordering::Acquire);
+        idx_head == idx_tail
+    }
+
+    /// 当前队列中的元素个数
+    #[inline]
+    pub fn len(&self) -> usize {
+        let idx_tail = self.idx_tail.load(Ordering::Acquire) & !CLAIMED;
+        let idx_head = self.idx_head.load(Ordering::Acquire);
+        Self::distance(idx_tail, idx_head)
+    }
+
+    /// 最多可容纳的元素个数，等于缓冲区大小
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        if SIZE == DYNAMIC {
+            self.m_heap.len()
+        } else {
+            SIZE
+        }
+    }
+
+    /// 剩余可写入的元素个数
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.capacity()
+    }
+
+    /// 是否为覆盖模式
+    #[inline]
+    pub fn is_overwrite(&self) -> bool {
+        self.overwrite
+    }
+
+    /// 最近一次取出的数据之前被覆盖的元素个数，非覆盖模式下总是0
+    #[inline]
+    pub fn missed(&self) -> usize {
+        // 只有读取者会修改，而读取需要 &mut self
+        self.rx_missed.with(|missed| unsafe { *missed })
+    }
+}
+
+impl<T: Copy, const SIZE: usize, S: Storage<T>> RingBuffer<T, SIZE, S> {
+    /// 批量写入，只写入队列能容纳的部分，返回写入的元素个数
+    #[inline]
+    pub fn push_slice(&mut self, values: &[T]) -> usize {
+        unsafe { Queue::push_slice_unchecked(self, values) }
+    }
+
+    /// 批量取出，最多填满 out，返回取出的元素个数
+    #[inline]
+    pub fn pop_into(&mut self, out: &mut [T]) -> usize {
+        unsafe { Queue::pop_into_unchecked(self, out) }
+    }
+}
+
+/// 只有一个写入者和一个读取者，写入一侧和读取一侧各自只会被一个线程访问
+unsafe impl<T, const SIZE: usize, S: Storage<T>> Queue for RingBuffer<T, SIZE, S> {
+    type Item = T;
+    type Storage = S;
+
+    #[inline]
+    fn len(&self) -> usize {
+        RingBuffer::len(self)
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        RingBuffer::capacity(self)
+    }
+
+    #[inline]
+    fn is_overwrite(&self) -> bool {
+        self.overwrite
+    }
+
+    #[inline]
+    fn missed(&self) -> usize {
+        RingBuffer::missed(self)
+    }
+
+    #[inline]
+    fn has_senders(&self) -> bool {
+        self.tx_alive.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    fn has_receivers(&self) -> bool {
+        self.rx_alive.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    fn release_sender(&self) {
+        self.tx_alive.store(false, Ordering::Release);
+    }
+
+    #[inline]
+    fn release_receiver(&self) {
+        self.rx_alive.store(false, Ordering::Release);
+    }
+
+    #[inline]
+    fn register_tx(&self, waker: &Waker) {
+        self.tx_signal.register(waker);
+    }
+
+    #[inline]
+    fn notify_tx(&self) {
+        self.tx_signal.notify();
+    }
+
+    #[inline]
+    fn register_rx(&self, waker: &Waker) {
+        self.rx_signal.register(waker);
+    }
+
+    #[inline]
+    fn notify_rx(&self) {
+        self.rx_signal.notify();
+    }
+
+    /// 覆盖模式下队列已满时丢弃最早的数据，不会失败
+    unsafe fn push_unchecked(&self, value: T) -> Result<(), T> {
+        match self.reserve_unchecked() {
+            Some(head) => {
+                self.slot(head).with_mut(|slot| S::write(&mut *slot, value));
+                self.commit_unchecked(head);
+                Ok(())
+            }
+            None if self.overwrite => {
+                // 最早的数据被读取者占用，只能丢弃新写入的数据
+                drop(value);
+                self.missed.fetch_add(1, Ordering::Release);
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+
+    /// 覆盖模式下先丢弃最早的数据腾出空位，只有最早的数据被 `Peek` 或 `ReadGuard` 占用时才返回 None
+    unsafe fn reserve_unchecked(&self) -> Option<usize> {
+        let head = self.idx_head.load(Ordering::Acquire);
+        if self.overwrite {
+            return self.evict_oldest(head).then_some(head);
+        }
+        if Self::distance(self.idx_tail.load(Ordering::Acquire), head) == self.capacity() {
+            return None;
+        }
+        Some(head)
+    }
+
+    #[inline]
+    unsafe fn slot_unchecked(&self, head: usize) -> &mut S::Slot {
+        self.slot(head).with_mut(|slot| &mut *slot)
+    }
+
+    /// 推进idx_head
+    #[inline]
+    unsafe fn commit_unchecked(&self, head: usize) {
+        self.idx_head.store(Self::next_idx(head), Ordering::Release);
+    }
+
+    #[inline]
+    unsafe fn pop_unchecked(&self) -> Option<T> {
+        let tail = self.claim(READING)?;
+        Some(self.consume_unchecked(tail))
+    }
+
+    /// 覆盖模式下在idx_tail上标记PINNED，此时队列已满的话写入者丢弃新写入的数据
+    #[inline]
+    unsafe fn claim_unchecked(&self) -> Option<usize> {
+        self.claim(PINNED)
+    }
+
+    #[inline]
+    unsafe fn claimed_unchecked(&self, tail: usize) -> &mut T {
+        self.slot(tail).with_mut(|slot| S::get_mut(&mut *slot))
+    }
+
+    /// 推进idx_tail，同时清除标记
+    unsafe fn consume_unchecked(&self, tail: usize) -> T {
+        let res = self.slot(tail).with_mut(|slot| S::take(&mut *slot));
+        self.idx_tail.store(Self::next_idx(tail), Ordering::Release);
+        if self.overwrite {
+            self.rx_missed.with_mut(|missed| *missed = self.missed.swap(0, Ordering::Acquire));
+        }
+        res
+    }
+
+    /// 非覆盖模式下没有标记，不需要做什么
+    #[inline]
+    unsafe fn release_unchecked(&self, tail: usize) {
+        if self.overwrite {
+            self.idx_tail.store(tail, Ordering::Release);
+        }
+    }
+
+    /// 在回绕处分成两段，全部写完之后只更新一次idx_head
+    /// 覆盖模式下逐个写入，队列已满时覆盖最早的数据
+    unsafe fn push_slice_unchecked(&self, values: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        if self.overwrite {
+            for value in values {
+                let _ = self.push_unchecked(*value);
+            }
+            return values.len();
+        }
+        let head = self.idx_head.load(Ordering::Acquire);
+        let tail = self.idx_tail.load(Ordering::Acquire);
+        let free = self.capacity() - Self::distance(tail, head);
+        let n = free.min(values.len());
+        let start = self.ring_idx(head);
+        let (first, second) = values[..n].split_at(n.min(self.capacity() - start));
+        for (slot, value) in self.slots()[start..].iter().zip(first) {
+            slot.with_mut(|slot| S::write(&mut *slot, *value));
+        }
+        for (slot, value) in self.slots().iter().zip(second) {
+            slot.with_mut(|slot| S::write(&mut *slot, *value));
+        }
+        self.idx_head.store(head.wrapping_add(n) & IDX_MASK, Ordering::Release);
+        n
+    }
+
+    /// 在回绕处分成两段，全部读完之后只更新一次idx_tail
+    /// 覆盖模式下逐个取出，防止写入者同时丢弃正在读取的数据
+    unsafe fn pop_into_unchecked(&self, out: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        if self.overwrite {
+            let mut n = 0;
+            while n < out.len() {
+                match self.pop_unchecked() {
+                    Some(value) => out[n] = value,
+                    None => break,
+                }
+                n += 1;
+            }
+            return n;
+        }
+        let tail = self.idx_tail.load(Ordering::Acquire);
+        let head = self.idx_head.load(Ordering::Acquire);
+        let n = Self::distance(tail, head).min(out.len());
+        let start = self.ring_idx(tail);
+        let (first, second) = out[..n].split_at_mut(n.min(self.capacity() - start));
+        for (value, slot) in first.iter_mut().zip(&self.slots()[start..]) {
+            *value = slot.with_mut(|slot| S::take(&mut *slot));
+        }
+        for (value, slot) in second.iter_mut().zip(self.slots()) {
+            *value = slot.with_mut(|slot| S::take(&mut *slot));
+        }
+        self.idx_tail.store(tail.wrapping_add(n) & IDX_MASK, Ordering::Release);
+        n
+    }
+}
+
+/// 这里采用Reader 和Writer的分离实现
+/// 由于Reader没有实现Clone，所以Reader不能共享所有权
+/// 由于Writer没有实现Clone，所以Writer不能共享所有权
+/// 因此，就实现了 单生产者-单消费者 模式
+pub type RingBufferSender<T, const SIZE: usize, S = Uninit> = Sender<RingBuffer<T, SIZE, S>>;
+
+/// 同 `RingBufferSender`，读取者也只能有一个
+pub type RingBufferReceiver<T, const SIZE: usize, S = Uninit> = Receiver<RingBuffer<T, SIZE, S>>;
+
+/// `RingBufferSender::reserve` 预留的槽位
+pub type WriteGuard<'a, T, const SIZE: usize, S = Uninit> = handle::WriteGuard<'a, RingBuffer<T, SIZE, S>>;
+
+/// `RingBufferReceiver::pop_guard` 占用的最早的数据
+pub type ReadGuard<'a, T, const SIZE: usize, S = Uninit> = handle::ReadGuard<'a, RingBuffer<T, SIZE, S>>;
+
+/// `RingBufferReceiver::peek` 查看的最早的数据
+pub type Peek<'a, T, const SIZE: usize, S = Uninit> = handle::Peek<'a, RingBuffer<T, SIZE, S>>;
+
+/// 按切片整段访问槽位，loom 的 UnsafeCell 和 S::Slot 的内存布局不同，以 `--cfg loom` 编译时没有这部分
+#[cfg(not(loom))]
+pub use chunk::{ReadChunk, WriteChunk};
+
+#[cfg(not(loom))]
+mod chunk {
+    use std::marker::PhantomData;
+    use crate::storage::{Defaulted, SliceStorage};
+    use super::*;
+
+    impl<T, const SIZE: usize, S: SliceStorage<T>> RingBuffer<T, SIZE, S> {
+        /// 第 idx 个槽位的指针，UnsafeCell<S::Slot> 和 S::Slot 的内存布局相同，所以相邻槽位可以整段访问
+        #[inline]
+        fn slot_ptr(&self, idx: usize) -> *mut S::Slot {
+            self.slots()[idx..].as_ptr() as *mut S::Slot
+        }
+
+        /// 从索引 start 开始的 len 个槽位，在回绕处分成两段
+        ///
+        /// # Safety
+        /// 这些槽位在返回的切片存活期间只能由调用者访问
+        #[allow(clippy::mut_from_ref)]
+        unsafe fn regions(&self, start: usize, len: usize) -> (&mut [S::Slot], &mut [S::Slot]) {
+            let start = self.ring_idx(start);
+            let first = len.min(self.capacity() - start);
+            (
+                std::slice::from_raw_parts_mut(self.slot_ptr(start), first),
+                std::slice::from_raw_parts_mut(self.slot_ptr(0), len - first),
+            )
+        }
+    }
+
+    impl<T, const SIZE: usize, S: SliceStorage<T>> Sender<RingBuffer<T, SIZE, S>> {
+        /// 预留最多 n 个连续的空槽，通过 `WriteChunk::as_mut_slices` 直接写入，提交之后读取者才能看到，
+        /// 没有提交就被释放时放弃写入。`Uninit` 存储时空槽是未初始化的 `MaybeUninit<T>`，`Defaulted` 存储时是默认值。
+        /// 队列已满时返回 `Error::Full`，读取者已经被释放时返回 `Error::Disconnected`，
+        /// 写入端已经关闭时返回 `Error::Closed`
+        /// 覆盖模式下也只预留空闲的槽位，不会丢弃最早的数据
+        pub fn write_chunk(&mut self, n: usize) -> Result<WriteChunk<'_, T, SIZE, S>, Error> {
+            self.check_open()?;
+            let len = self.remaining().min(n);
+            if len == 0 && n > 0 {
+                return Err(Error::Full);
+            }
+            // 写入需要 &mut self，chunk 存活期间不会有其它写入，读取者也不会访问还没有提交的空槽
+            let inner = self.queue();
+            Ok(WriteChunk {
+                inner,
+                start: inner.idx_head.load(Ordering::Relaxed),
+                len,
+                committed: false,
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    /// `RingBufferSender::write_chunk` 预留的空闲区域，提交之前读取者看不到
+    pub struct WriteChunk<'a, T, const SIZE: usize, S: SliceStorage<T> = Uninit> {
+        inner: &'a RingBuffer<T, SIZE, S>,
+        start: usize,
+        len: usize,
+        committed: bool,
+        _marker: PhantomData<*mut ()>,
+    }
+
+    unsafe impl<T: Send, const SIZE: usize, S: SliceStorage<T>> Send for WriteChunk<'_, T, SIZE, S> {}
+    unsafe impl<T: Sync, const SIZE: usize, S: SliceStorage<T>> Sync for WriteChunk<'_, T, SIZE, S> {}
+
+    impl<T, const SIZE: usize, S: SliceStorage<T>> WriteChunk<'_, T, SIZE, S> {
+        /// 预留的槽位个数
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        /// 空闲区域在回绕处分成的两段，第二段可能为空
+        #[inline]
+        pub fn as_mut_slices(&mut self) -> (&mut [S::Slot], &mut [S::Slot]) {
+            unsafe { self.inner.regions(self.start, self.len) }
+        }
+
+        /// 发布前 n 个槽位
+        ///
+        /// # Safety
+        /// 前 n 个槽位必须已经写入数据
+        unsafe fn commit_unchecked(&mut self, n: usize) {
+            assert!(n <= self.len, "commit {} items on a chunk of {}", n, self.len);
+            self.reset(n);
+            self.committed = true;
+            self.inner.idx_head.store(self.start.wrapping_add(n) & IDX_MASK, Ordering::Release);
+            self.inner.rx_signal.notify();
+        }
+
+        /// `Defaulted` 存储时恢复没有提交的槽位为默认值，避免下一次预留看到写了一半的数据，
+        /// `Uninit` 存储时没有提交的槽位被当作空槽，其中已经写入的数据不会被析构
+        fn reset(&mut self, committed: usize) {
+            if S::PREFILLED {
+                let (first, second) = self.as_mut_slices();
+                first.iter_mut().chain(second).skip(committed).for_each(|slot| *slot = S::vacant());
+            }
+        }
+    }
+
+    impl<T: Default, const SIZE: usize> WriteChunk<'_, T, SIZE, Defaulted> {
+        /// 提交前 n 个槽位，读取者随后可以取出，其余槽位放弃写入
+        pub fn commit(mut self, n: usize) {
+            // Defaulted 的空槽总是有效的 T
+            unsafe { self.commit_unchecked(n) }
+        }
+
+        /// 提交全部预留的槽位
+        #[inline]
+        pub fn commit_all(self) {
+            let n = self.len;
+            self.commit(n);
+        }
+    }
+
+    impl<T, const SIZE: usize> WriteChunk<'_, T, SIZE, Uninit> {
+        /// 提交前 n 个槽位，读取者随后可以取出，其余槽位放弃写入
+        ///
+        /// # Safety
+        /// 前 n 个槽位必须已经通过 `MaybeUninit::write` 写入数据
+        pub unsafe fn commit(mut self, n: usize) {
+            self.commit_unchecked(n)
+        }
+
+        /// 提交全部预留的槽位
+        ///
+        /// # Safety
+        /// 全部槽位必须已经通过 `MaybeUninit::write` 写入数据
+        #[inline]
+        pub unsafe fn commit_all(self) {
+            let n = self.len;
+            self.commit(n);
+        }
+    }
+
+    impl<T, const SIZE: usize, S: SliceStorage<T>> Drop for WriteChunk<'_, T, SIZE, S> {
+        fn drop(&mut self) {
+            if !self.committed {
+                self.reset(0);
+            }
+        }
+    }
+
+    impl<T, const SIZE: usize, S: SliceStorage<T>> Receiver<RingBuffer<T, SIZE, S>> {
+        /// 占用最多 n 个已写入的元素，通过 `ReadChunk::as_slices` 直接读取，提交之后才从队列中移除，
+        /// 队列为空时返回 `Error::Empty`，写入者已经被释放并且队列中的数据已经取完时返回 `Error::Disconnected`
+        /// 覆盖模式下和 `peek` 一样，被占用的数据会一直保留到 chunk 提交或者被释放，期间队列已满时写入者丢弃新写入的数据
+        pub fn read_chunk(&mut self, n: usize) -> Result<ReadChunk<'_, T, SIZE, S>, Error> {
+            let inner = self.queue();
+            let mut len = self.len().min(n);
+            if len == 0 && n > 0 {
+                if !self.is_disconnected() {
+                    return Err(Error::Empty);
+                }
+                // 写入者在释放之前写入的数据对这里可见，需要再检查一次
+                len = self.len().min(n);
+                if len == 0 {
+                    return Err(Error::Disconnected);
+                }
+            }
+            if len == 0 {
+                return Ok(ReadChunk {
+                    inner,
+                    start: inner.idx_tail.load(Ordering::Acquire) & !CLAIMED,
+                    len,
+                    committed: false,
+                    _marker: PhantomData,
+                });
+            }
+            // 读取需要 &mut self，chunk 存活期间不会有其它读取，覆盖模式下标记PINNED之后写入者也不会再丢弃最早的数据
+            // 覆盖模式下写入者可能在检查长度之后丢弃了仅剩的数据，此时队列为空
+            let Some(start) = (unsafe { inner.claim(PINNED) }) else {
+                return Err(Error::Empty);
+            };
+            // 标记之前写入者可能已经丢弃了一部分数据
+            let len = RingBuffer::<T, SIZE, S>::distance(start, inner.idx_head.load(Ordering::Acquire)).min(n);
+            Ok(ReadChunk {
+                inner,
+                start,
+                len,
+                committed: false,
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    /// `RingBufferReceiver::read_chunk` 占用的已写入区域，提交之前数据留在队列中，
+    /// 没有提交就被释放时放弃占用，覆盖模式下写入者恢复丢弃最早的数据
+    pub struct ReadChunk<'a, T, const SIZE: usize, S: SliceStorage<T> = Uninit> {
+        inner: &'a RingBuffer<T, SIZE, S>,
+        start: usize,
+        len: usize,
+        committed: bool,
+        _marker: PhantomData<*mut ()>,
+    }
+
+    unsafe impl<T: Send, const SIZE: usize, S: SliceStorage<T>> Send for ReadChunk<'_, T, SIZE, S> {}
+    unsafe impl<T: Sync, const SIZE: usize, S: SliceStorage<T>> Sync for ReadChunk<'_, T, SIZE, S> {}
+
+    impl<T, const SIZE: usize, S: SliceStorage<T>> ReadChunk<'_, T, SIZE, S> {
+        /// 占用的元素个数
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        /// 已写入区域在回绕处分成的两段，第二段可能为空
+        #[inline]
+        pub fn as_slices(&self) -> (&[T], &[T]) {
+            let start = self.inner.ring_idx(self.start);
+            let first = self.len.min(self.inner.capacity() - start);
+            // 已写入的槽位可以当作 T 访问
+            unsafe {
+                (
+                    std::slice::from_raw_parts(self.inner.slot_ptr(start).cast(), first),
+                    std::slice::from_raw_parts(self.inner.slot_ptr(0).cast(), self.len - first),
+                )
+            }
+        }
+
+        /// 同 `as_slices`，可以原地修改
+        #[inline]
+        pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+            let (first, second) = unsafe { self.inner.regions(self.start, self.len) };
+            (Self::cast(first), Self::cast(second))
+        }
+
+        /// 已写入的槽位可以当作 T 访问
+        #[inline]
+        fn cast(slots: &mut [S::Slot]) -> &mut [T] {
+            unsafe { std::slice::from_raw_parts_mut(slots.as_mut_ptr().cast(), slots.len()) }
+        }
+
+        /// 从队列中移除前 n 个元素，其余元素留在队列中，n 为 0 时和直接释放一样只放弃占用
+        pub fn commit(mut self, n: usize) {
+            assert!(n <= self.len, "commit {} items on a chunk of {}", n, self.len);
+            if n == 0 {
+                return;
+            }
+            if std::mem::needs_drop::<T>() {
+                // 及时释放被移除元素持有的资源，Uninit 的槽位随后变为空槽
+                let (first, second) = unsafe { self.inner.regions(self.start, n) };
+                first.iter_mut().chain(second).for_each(|slot| unsafe { S::clear(slot) });
+            }
+            self.committed = true;
+            let inner = self.inner;
+            inner.idx_tail.store(self.start.wrapping_add(n) & IDX_MASK, Ordering::Release);
+            if inner.overwrite {
+                inner.rx_missed.with_mut(|missed| unsafe { *missed = inner.missed.swap(0, Ordering::Acquire) });
+            }
+            inner.tx_signal.notify();
+        }
+
+        /// 从队列中移除全部占用的元素
+        #[inline]
+        pub fn commit_all(self) {
+            let n = self.len;
+            self.commit(n);
+        }
+    }
+
+    impl<T, const SIZE: usize, S: SliceStorage<T>> Drop for ReadChunk<'_, T, SIZE, S> {
+        fn drop(&mut self) {
+            // 空的 chunk 没有占用槽位
+            if !self.committed && self.len > 0 {
+                unsafe { self.inner.release_unchecked(self.start) };
+            }
+        }
+    }
+}
+
+/// 创建 单生产者-单消费者 环形缓冲区，SIZE 必须是2的幂，可以容纳 SIZE 个元素
+/// ```
+/// let (mut tx, _rx) = lockfree::queue::ringbuffer::<u32, 8>();
+/// for i in 0..8 {
+///     tx.push(i).unwrap();
+/// }
+/// assert!(tx.is_full());
+/// ```
+/// SIZE 不是2的幂时无法通过编译：
+/// ```compile_fail
+/// let (tx, rx) = lockfree::queue::ringbuffer::<u32, 6>();
+/// ```
+pub fn ringbuffer<T, const SIZE: usize>() -> (RingBufferSender<T, SIZE>, RingBufferReceiver<T, SIZE>)
+{
+    ringbuffer_with()
+}
+
+/// 创建覆盖模式的环形缓冲区，队列已满时写入者覆盖最早的数据，不会阻塞也不会返回 Full，
+/// 读取者通过 `RingBufferReceiver::missed` 得知错过了多少数据
+pub fn ringbuffer_overwrite<T, const SIZE: usize>() -> (RingBufferSender<T, SIZE>, RingBufferReceiver<T, SIZE>)
+{
+    ringbuffer_overwrite_with()
+}
+
+/// 同 `ringbuffer`，通过 S 指定槽位的存储策略
+/// ```
+/// use lockfree::storage::Defaulted;
+///
+/// let (mut tx, mut rx) = lockfree::queue::ringbuffer_with::<u8, 8, Defaulted>();
+/// let mut chunk = tx.write_chunk(3).unwrap();
+/// chunk.as_mut_slices().0.copy_from_slice(b"abc");
+/// chunk.commit_all();
+/// assert_eq!(rx.read_chunk(3).unwrap().as_slices(), (&b"abc"[..], &b""[..]));
+/// ```
+pub fn ringbuffer_with<T, const SIZE: usize, S: Storage<T>>() -> (RingBufferSender<T, SIZE, S>, RingBufferReceiver<T, SIZE, S>)
+{
+    split(RingBuffer::new_arc(false))
+}
+
+/// 同 `ringbuffer_overwrite`，通过 S 指定槽位的存储策略
+pub fn ringbuffer_overwrite_with<T, const SIZE: usize, S: Storage<T>>() -> (RingBufferSender<T, SIZE, S>, RingBufferReceiver<T, SIZE, S>)
+{
+    split(RingBuffer::new_arc(true))
+}
+
+/// 创建容量在运行时指定的环形缓冲区，可以容纳至少 capacity 个元素，缓冲区大小向上取整为2的幂，
+/// capacity 为0或者取整后溢出时返回 `CapacityError`
+/// ```
+/// let (tx, _rx) = lockfree::queue::ringbuffer_with_capacity::<u32>(100).unwrap();
+/// assert_eq!(tx.capacity(), 128);
+/// ```
+pub fn ringbuffer_with_capacity<T>(capacity: usize) -> Result<(RingBufferSender<T, DYNAMIC>, RingBufferReceiver<T, DYNAMIC>), CapacityError>
+{
+    Ok(split(RingBuffer::with_capacity(capacity, false)?))
+}
+
+/// 同 `ringbuffer_with_capacity`，创建覆盖模式的环形缓冲区
+pub fn ringbuffer_overwrite_with_capacity<T>(capacity: usize) -> Result<(RingBufferSender<T, DYNAMIC>, RingBufferReceiver<T, DYNAMIC>), CapacityError>
+{
+    Ok(split(RingBuffer::with_capacity(capacity, true)?))
+}
+
+fn split<T, const SIZE: usize, S: Storage<T>>(ring: Arc<RingBuffer<T, SIZE, S>>) -> (RingBufferSender<T, SIZE, S>, RingBufferReceiver<T, SIZE, S>)
+{
+    (Sender::new(ring.clone()), Receiver::new(ring))
+}
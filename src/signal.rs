@@ -0,0 +1,230 @@
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Instant;
+use crossbeam_utils::Backoff;
+use crate::sync::{fence, AtomicBool, AtomicUsize, Mutex, Ordering, UnsafeCell};
+
+const WAITING: usize = 0;
+const REGISTERING: usize = 1;
+const WAKING: usize = 2;
+
+/// 单个等待者的唤醒槽
+/// 等待的一方登记自己的Waker，另一方在修改索引之后调用notify唤醒它。
+/// 由于只在两个线程之间共享，所以同一时刻只会有一个等待者登记，
+/// 没有等待者时notify只有一次屏障和一次原子读，不会产生读改写操作。
+pub(crate) struct Signal {
+    state: AtomicUsize,
+    armed: AtomicBool,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+unsafe impl Send for Signal {}
+
+unsafe impl Sync for Signal {}
+
+impl Signal {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(WAITING),
+            armed: AtomicBool::new(false),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// 登记等待者，登记之后调用方必须重新检查一次条件再进入等待
+    pub(crate) fn register(&self, waker: &Waker) {
+        match self.state.compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => {
+                // 只有把state从WAITING改为REGISTERING或者WAKING的一方才能访问waker
+                self.waker.with_mut(|slot| match unsafe { &mut *slot } {
+                    Some(old) if old.will_wake(waker) => {}
+                    slot => *slot = Some(waker.clone()),
+                });
+                self.armed.store(true, Ordering::Relaxed);
+                if self.state.compare_exchange(REGISTERING, WAITING, Ordering::AcqRel, Ordering::Acquire).is_err() {
+                    // 登记期间另一方已经发出了唤醒，这里代为唤醒
+                    let waker = self.waker.with_mut(|slot| unsafe { (*slot).take() });
+                    self.armed.store(false, Ordering::Relaxed);
+                    self.state.swap(WAITING, Ordering::AcqRel);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            Err(WAKING) => waker.wake_by_ref(),
+            Err(_) => {}
+        }
+        // 与notify中的屏障配对，保证要么等待者看到新的索引，要么通知者看到armed
+        fence(Ordering::SeqCst);
+    }
+
+    /// 修改索引之后调用，如果有等待者则唤醒
+    #[inline]
+    pub(crate) fn notify(&self) {
+        fence(Ordering::SeqCst);
+        if self.armed.load(Ordering::Relaxed) {
+            self.wake();
+        }
+    }
+
+    #[cold]
+    fn wake(&self) {
+        if let WAITING = self.state.fetch_or(WAKING, Ordering::AcqRel) {
+            self.armed.store(false, Ordering::Relaxed);
+            let waker = self.waker.with_mut(|slot| unsafe { (*slot).take() });
+            self.state.fetch_and(!WAKING, Ordering::Release);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Signal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Signal")
+            .field("armed", &self.armed.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+/// 可以同时有多个等待者的唤醒列表，用于多个写入者或者多个读取者共享同一侧的情况
+/// 只有登记等待者时才会加锁，没有等待者时notify和Signal一样只有一次屏障和一次原子读
+#[derive(Debug, Default)]
+pub(crate) struct WaitList {
+    waiting: AtomicUsize,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl WaitList {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self {
+            waiting: AtomicUsize::new(0),
+            wakers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 登记等待者，登记之后调用方必须重新检查一次条件再进入等待
+    pub(crate) fn register(&self, waker: &Waker) {
+        {
+            let mut wakers = self.wakers.lock().unwrap_or_else(|e| e.into_inner());
+            if !wakers.iter().any(|w| w.will_wake(waker)) {
+                wakers.push(waker.clone());
+            }
+            self.waiting.store(wakers.len(), Ordering::Relaxed);
+        }
+        fence(Ordering::SeqCst);
+    }
+
+    /// 修改索引之后调用，唤醒所有等待者，由它们重新竞争
+    #[inline]
+    pub(crate) fn notify(&self) {
+        fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) != 0 {
+            self.wake_all();
+        }
+    }
+
+    #[cold]
+    fn wake_all(&self) {
+        let wakers = {
+            let mut wakers = self.wakers.lock().unwrap_or_else(|e| e.into_inner());
+            self.waiting.store(0, Ordering::Relaxed);
+            std::mem::take(&mut *wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// 等待者登记Waker的位置，Signal用于单个等待者，WaitList用于多个等待者
+pub(crate) trait Register {
+    fn register(&self, waker: &Waker);
+}
+
+impl Register for Signal {
+    #[inline]
+    fn register(&self, waker: &Waker) {
+        Signal::register(self, waker)
+    }
+}
+
+impl Register for WaitList {
+    #[inline]
+    fn register(&self, waker: &Waker) {
+        WaitList::register(self, waker)
+    }
+}
+
+/// 唤醒时unpark对应线程的Waker
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// 阻塞当前线程，直到 f 返回 Some
+pub(crate) fn block_on<R>(signal: &impl Register, f: impl FnMut() -> Option<R>) -> R {
+    match wait(signal, None, f) {
+        Some(r) => r,
+        None => unreachable!("waiting without deadline never times out"),
+    }
+}
+
+/// 阻塞当前线程，直到 f 返回 Some 或者超过 deadline，超时返回 None
+/// 先自旋若干次，仍然不满足时登记到 signal 并挂起线程，等待另一方唤醒
+pub(crate) fn wait<R>(signal: &impl Register, deadline: Option<Instant>, mut f: impl FnMut() -> Option<R>) -> Option<R> {
+    let backoff = Backoff::new();
+    loop {
+        if let Some(r) = f() {
+            return Some(r);
+        }
+        if backoff.is_completed() {
+            break;
+        }
+        backoff.snooze();
+    }
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    loop {
+        signal.register(&waker);
+        if let Some(r) = f() {
+            return Some(r);
+        }
+        match deadline {
+            None => thread::park(),
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return None;
+                }
+                thread::park_timeout(deadline - now);
+            }
+        }
+        if let Some(r) = f() {
+            return Some(r);
+        }
+    }
+}
+
+/// 异步版本的等待，f 返回 None 时登记当前任务的 Waker 并返回 Pending
+pub(crate) fn poll<R>(signal: &impl Register, cx: &mut Context<'_>, mut f: impl FnMut() -> Option<R>) -> Poll<R> {
+    if let Some(r) = f() {
+        return Poll::Ready(r);
+    }
+    signal.register(cx.waker());
+    match f() {
+        Some(r) => Poll::Ready(r),
+        None => Poll::Pending,
+    }
+}
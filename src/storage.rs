@@ -0,0 +1,213 @@
+//! 槽位的存储策略，决定空槽中放的是什么，以及数据如何写入和取出
+//!
+//! - `Uninit`：`MaybeUninit<T>`，空槽不占用任何值，对 T 没有要求，由队列记录哪些槽位已写入
+//! - `Optional`：`Option<T>`，空槽为 None，槽位自己记录是否有值
+//! - `Defaulted`：T 本身，空槽为 `T::default()`，槽位总是有效的值，空槽也可以直接按切片访问
+
+use std::mem::MaybeUninit;
+
+/// 槽位的存储策略
+///
+/// # Safety
+/// `take`、`get`、`get_mut`、`clear` 只能用于已写入的槽位，`PREFILLED` 为 true 时
+/// 空槽也必须是有效的 T，T: Send 时 Slot 也必须是 Send，实现者必须保证这些约定成立
+pub unsafe trait Storage<T> {
+    /// 槽位中实际存放的类型
+    type Slot;
+
+    /// 空槽本身也是有效的 T，可以直接读写
+    const PREFILLED: bool;
+
+    /// 创建空槽
+    fn vacant() -> Self::Slot;
+
+    /// 创建写入了 value 的槽位
+    fn fill(value: T) -> Self::Slot;
+
+    /// 向空槽写入数据，返回写入的数据
+    ///
+    /// # Safety
+    /// slot 必须是空槽
+    unsafe fn write(slot: &mut Self::Slot, value: T) -> &mut T;
+
+    /// 取出已写入槽位中的数据，槽位随后变为空槽
+    ///
+    /// # Safety
+    /// slot 必须已经写入数据
+    unsafe fn take(slot: &mut Self::Slot) -> T;
+
+    /// # Safety
+    /// slot 必须已经写入数据
+    unsafe fn get(slot: &Self::Slot) -> &T;
+
+    /// # Safety
+    /// slot 必须已经写入数据
+    unsafe fn get_mut(slot: &mut Self::Slot) -> &mut T;
+
+    /// 析构已写入槽位中的数据，槽位随后变为空槽
+    ///
+    /// # Safety
+    /// slot 必须已经写入数据
+    #[inline]
+    unsafe fn clear(slot: &mut Self::Slot) {
+        drop(Self::take(slot));
+    }
+}
+
+/// 槽位和 T 的内存布局相同（`T` 或 `MaybeUninit<T>`），相邻的已写入槽位可以当作 `[T]` 整段访问，
+/// `WriteChunk` 和 `ReadChunk` 需要这样的存储策略
+///
+/// # Safety
+/// 已写入的 `[Self::Slot]` 必须可以重新解释为 `[T]`
+pub unsafe trait SliceStorage<T>: Storage<T> {}
+
+/// 槽位总是处于有效状态，直接替换或者析构槽位都是安全的，`LockFreeValue` 需要这样的存储策略
+pub trait ValueStorage<T>: Storage<T> {
+    /// 取出槽位中的数据，空槽返回 None
+    fn take_value(slot: &mut Self::Slot) -> Option<T>;
+}
+
+/// 使用 `MaybeUninit<T>` 存储，对 T 没有要求，只能用于自己记录已写入区间的队列
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Uninit;
+
+unsafe impl<T> Storage<T> for Uninit {
+    type Slot = MaybeUninit<T>;
+
+    const PREFILLED: bool = false;
+
+    #[inline]
+    fn vacant() -> MaybeUninit<T> {
+        MaybeUninit::uninit()
+    }
+
+    #[inline]
+    fn fill(value: T) -> MaybeUninit<T> {
+        MaybeUninit::new(value)
+    }
+
+    #[inline]
+    unsafe fn write(slot: &mut MaybeUninit<T>, value: T) -> &mut T {
+        slot.write(value)
+    }
+
+    #[inline]
+    unsafe fn take(slot: &mut MaybeUninit<T>) -> T {
+        slot.assume_init_read()
+    }
+
+    #[inline]
+    unsafe fn get(slot: &MaybeUninit<T>) -> &T {
+        slot.assume_init_ref()
+    }
+
+    #[inline]
+    unsafe fn get_mut(slot: &mut MaybeUninit<T>) -> &mut T {
+        slot.assume_init_mut()
+    }
+
+    #[inline]
+    unsafe fn clear(slot: &mut MaybeUninit<T>) {
+        slot.assume_init_drop()
+    }
+}
+
+unsafe impl<T> SliceStorage<T> for Uninit {}
+
+/// 使用 `Option<T>` 存储，空槽为 None
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Optional;
+
+unsafe impl<T> Storage<T> for Optional {
+    type Slot = Option<T>;
+
+    const PREFILLED: bool = false;
+
+    #[inline]
+    fn vacant() -> Option<T> {
+        None
+    }
+
+    #[inline]
+    fn fill(value: T) -> Option<T> {
+        Some(value)
+    }
+
+    #[inline]
+    unsafe fn write(slot: &mut Option<T>, value: T) -> &mut T {
+        slot.insert(value)
+    }
+
+    #[inline]
+    unsafe fn take(slot: &mut Option<T>) -> T {
+        slot.take().expect("slot is written")
+    }
+
+    #[inline]
+    unsafe fn get(slot: &Option<T>) -> &T {
+        slot.as_ref().expect("slot is written")
+    }
+
+    #[inline]
+    unsafe fn get_mut(slot: &mut Option<T>) -> &mut T {
+        slot.as_mut().expect("slot is written")
+    }
+}
+
+impl<T> ValueStorage<T> for Optional {
+    #[inline]
+    fn take_value(slot: &mut Option<T>) -> Option<T> {
+        slot.take()
+    }
+}
+
+/// 直接存储 T，空槽为 `T::default()`，取出之后槽位恢复为默认值
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Defaulted;
+
+unsafe impl<T: Default> Storage<T> for Defaulted {
+    type Slot = T;
+
+    const PREFILLED: bool = true;
+
+    #[inline]
+    fn vacant() -> T {
+        T::default()
+    }
+
+    #[inline]
+    fn fill(value: T) -> T {
+        value
+    }
+
+    #[inline]
+    unsafe fn write(slot: &mut T, value: T) -> &mut T {
+        // 空槽中的默认值可能持有资源，需要析构
+        *slot = value;
+        slot
+    }
+
+    #[inline]
+    unsafe fn take(slot: &mut T) -> T {
+        std::mem::take(slot)
+    }
+
+    #[inline]
+    unsafe fn get(slot: &T) -> &T {
+        slot
+    }
+
+    #[inline]
+    unsafe fn get_mut(slot: &mut T) -> &mut T {
+        slot
+    }
+}
+
+unsafe impl<T: Default> SliceStorage<T> for Defaulted {}
+
+impl<T: Default> ValueStorage<T> for Defaulted {
+    #[inline]
+    fn take_value(slot: &mut T) -> Option<T> {
+        Some(std::mem::take(slot))
+    }
+}
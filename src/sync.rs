@@ -0,0 +1,63 @@
+//! `RingBuffer`、`LockFreeValue` 以及唤醒等待者的 `Signal` 使用的同步原语，以 `--cfg loom` 编译时换成 loom 的实现，
+//! 由 loom 穷举所有线程交错和内存序允许的结果，见 tests/loom.rs
+//!
+//! 槽位也使用这里的 `UnsafeCell`，loom 会检查读写两端对同一槽位的访问是否有先后关系。
+//! 标准库下的 `UnsafeCell` 和 T 内存布局相同，`RingBuffer` 的相邻槽位可以按切片整段访问，
+//! loom 的 `UnsafeCell` 带有额外的记录，不能按切片访问，所以以 `--cfg loom` 编译时没有 `WriteChunk` 和 `ReadChunk`。
+
+#[cfg(not(loom))]
+pub(crate) use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(not(loom))]
+pub(crate) use std::sync::Mutex;
+
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(loom)]
+pub(crate) use loom::sync::Mutex;
+
+#[cfg(loom)]
+pub(crate) use loom::cell::UnsafeCell;
+
+/// 和 loom 的 UnsafeCell 接口一致，访问都通过闭包进行，loom 据此记录每次访问的起止
+#[cfg(not(loom))]
+#[repr(transparent)]
+pub(crate) struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    #[inline]
+    pub(crate) fn new(value: T) -> Self {
+        Self(std::cell::UnsafeCell::new(value))
+    }
+
+    #[inline]
+    pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    #[inline]
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
+#[cfg(not(loom))]
+impl<T> std::fmt::Debug for UnsafeCell<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnsafeCell").finish_non_exhaustive()
+    }
+}
+
+/// 自旋等待另一方完成，loom 下必须主动让出，否则模型会一直调度自旋的线程
+#[inline]
+pub(crate) fn snooze(backoff: &crossbeam_utils::Backoff) {
+    #[cfg(loom)]
+    {
+        let _ = backoff;
+        loom::thread::yield_now();
+    }
+    #[cfg(not(loom))]
+    backoff.snooze();
+}
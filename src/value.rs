@@ -0,0 +1,640 @@
+#![allow(dead_code)]
+
+use std::marker::PhantomData;
+use std::ptr::addr_of_mut;
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use crossbeam_utils::CachePadded;
+use crate::signal::{self, Signal};
+use crate::in_place::{self, init_array};
+use crate::error::{Error, SendError};
+use crate::storage::{Defaulted, Optional, ValueStorage};
+use crate::sync::{AtomicBool, AtomicUsize, Ordering, UnsafeCell};
+
+/// set_idx 存放在索引的低半部分，get_idx 存放在高半部分
+const GET_SHIFT: u32 = usize::BITS / 2;
+const SET_MASK: usize = (1 << GET_SHIFT) - 1;
+
+/// 槽位通过UnsafeCell实现内部可变，写入者通过next_idx_safe避开读取者正在使用的槽位
+/// 槽位中存放什么由存储策略 S 决定，默认的 `Optional` 为 `Option<T>`，取走之后为 None；
+/// `Defaulted` 直接存放 T，取走之后恢复为默认值，读取者还可以通过引用原地访问最新值
+/// set_idx（最新值所在的槽位）和 get_idx（读取者占用的槽位）放在同一个原子变量中，
+/// 读取者读到最新值的索引和占用它是同一次CAS，写入者因此总能看到读取者正在或者将要使用的槽位
+#[derive(Debug)]
+pub struct LockFreeValue<T, const ITEM_SIZE: usize, S: ValueStorage<T> = Optional> {
+    data: [UnsafeCell<S::Slot>; ITEM_SIZE],
+    idx: CachePadded<AtomicUsize>,
+    /// 读取者等待新值
+    signal: Signal,
+    /// 写入者是否存活
+    writer_alive: AtomicBool,
+    /// 读取者是否存活
+    reader_alive: AtomicBool,
+}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> LockFreeValue<T, SIZE, S>
+{
+    #[inline]
+    pub fn new() -> Self {
+        unsafe { in_place::new_value(|ptr| Self::init(ptr)) }
+    }
+
+    /// 直接在 Arc 的堆内存中创建，槽位数组不经过栈
+    fn new_arc() -> Arc<Self> {
+        unsafe { in_place::new_arc(|ptr| Self::init(ptr)) }
+    }
+
+    /// 逐个字段初始化 ptr 指向的内存
+    ///
+    /// # Safety
+    /// ptr 必须指向可写的未初始化内存
+    unsafe fn init(ptr: *mut Self) {
+        const { assert!(SIZE >= 2, "LockFreeValue SIZE must be at least 2") };
+        init_array(addr_of_mut!((*ptr).data), |_| UnsafeCell::new(S::vacant()));
+        addr_of_mut!((*ptr).idx).write(CachePadded::new(AtomicUsize::new(0)));
+        addr_of_mut!((*ptr).signal).write(Signal::new());
+        addr_of_mut!((*ptr).writer_alive).write(AtomicBool::new(true));
+        addr_of_mut!((*ptr).reader_alive).write(AtomicBool::new(true));
+    }
+}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> Default for LockFreeValue<T, SIZE, S> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 通过共享引用能调用的安全方法只读取原子索引或只读访问槽位，修改槽位需要 &mut self 或者 unsafe 方法，
+/// `at` 和 `Index` 通过共享引用交出 &S::Slot，所以槽位本身也必须是 Sync
+unsafe impl<T: Send + Sync, const SIZE: usize, S: ValueStorage<T>> Sync for LockFreeValue<T, SIZE, S> where S::Slot: Send + Sync {}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> LockFreeValue<T, SIZE, S>
+{
+    #[inline]
+    fn pack(set_idx: usize, get_idx: usize) -> usize {
+        set_idx | get_idx << GET_SHIFT
+    }
+
+    #[inline]
+    fn set_idx(idx: usize) -> usize {
+        idx & SET_MASK
+    }
+
+    #[inline]
+    fn get_idx(idx: usize) -> usize {
+        idx >> GET_SHIFT
+    }
+
+    /// 槽位 idx 的下一个槽位，SIZE 不要求是2的幂，所以比较之后回绕而不是取掩码
+    #[inline]
+    fn wrap_next(idx: usize) -> usize {
+        if idx + 1 == SIZE {
+            0
+        } else {
+            idx + 1
+        }
+    }
+
+    /// 既不是最新值、也不是读取者占用的槽位，SIZE 为2并且最新值还没有被读取时没有这样的槽位
+    #[inline]
+    fn free_idx(set_idx: usize, get_idx: usize) -> Option<usize> {
+        let mut next = Self::wrap_next(set_idx);
+        if next == get_idx {
+            next = Self::wrap_next(next);
+        }
+        (next != set_idx).then_some(next)
+    }
+
+    /// 缓冲区大小
+    #[inline]
+    pub fn size(&self) -> usize {
+        SIZE
+    }
+    /// 获取下一个位置的索引
+    #[inline]
+    pub fn next_idx(&self) -> usize {
+        Self::wrap_next(Self::set_idx(self.idx.load(Ordering::Acquire)))
+    }
+    /// 安全地获取下一个位置的索引，这将跳过最新值和读取者占用的槽位
+    /// SIZE 为2并且最新值还没有被读取时没有空闲的槽位，返回最新值所在的槽位，读取者可能正在占用它，这时只能通过 push 写入
+    #[inline]
+    pub fn next_idx_safe(&self) -> usize {
+        let idx = self.idx.load(Ordering::Acquire);
+        Self::free_idx(Self::set_idx(idx), Self::get_idx(idx)).unwrap_or(Self::set_idx(idx))
+    }
+
+    /// 放入最新值
+    #[inline]
+    pub fn push(&mut self, value: T) -> S::Slot {
+        unsafe { self.push_unchecked(value) }
+    }
+
+    /// 设置缓冲区数据
+    #[inline]
+    pub fn set_value(&mut self, idx: usize, value: T) -> S::Slot {
+        self.data[idx].with_mut(|slot| unsafe { std::mem::replace(&mut *slot, S::fill(value)) })
+    }
+
+    /// 设置下一个索引
+    #[inline]
+    pub fn set_next_idx(&mut self, next_idx: usize) {
+        self.publish(next_idx);
+    }
+
+    /// 最新值是否已经发生变化
+    #[inline]
+    pub fn changed(&self) -> bool {
+        let idx = self.idx.load(Ordering::Acquire);
+        Self::get_idx(idx) != Self::set_idx(idx)
+    }
+
+    /// 最新值是否没有发生变化
+    #[inline]
+    pub fn unchanged(&self) -> bool {
+        !self.changed()
+    }
+
+    /// 将获取值的索引更新到最新值的索引
+    #[inline]
+    pub fn update(&mut self) -> usize {
+        let set_idx = Self::set_idx(self.idx.load(Ordering::Acquire));
+        self.idx.store(Self::pack(set_idx, set_idx), Ordering::Release);
+        set_idx
+    }
+
+    /// 获取缓冲区数据
+    #[inline]
+    pub fn at(&self, idx: usize) -> &S::Slot {
+        self.data[idx].with(|slot| unsafe { &*slot })
+    }
+
+
+    /// 获取缓冲区数据可变
+    #[inline]
+    pub fn at_mut(&mut self, idx: usize) -> &mut S::Slot {
+        self.data[idx].with_mut(|slot| unsafe { &mut *slot })
+    }
+
+    /// 获取最新的数据，没有新值时返回 `Error::Empty`
+    #[inline]
+    pub fn get_last(&mut self) -> Result<T, Error> {
+        unsafe { self.get_last_unchecked() }
+    }
+
+    /// 最新值发生变化时返回 Ok，写入者已经被释放并且没有新值时返回 `Error::Disconnected`，否则返回 None
+    #[inline]
+    fn check_changed(&self) -> Option<Result<(), Error>> {
+        if self.changed() {
+            Some(Ok(()))
+        } else if !self.writer_alive.load(Ordering::Acquire) {
+            // 写入者在释放之前写入的数据对这里可见，需要再检查一次
+            Some(if self.changed() { Ok(()) } else { Err(Error::Disconnected) })
+        } else {
+            None
+        }
+    }
+
+    /// 通过共享引用放入最新值
+    ///
+    /// # Safety
+    /// 同一时刻只能有一个写入者
+    #[inline]
+    pub(crate) unsafe fn push_unchecked(&self, value: T) -> S::Slot {
+        let next = self.reserve_idx();
+        let old = self.set_value_unchecked(next, value);
+        self.publish(next);
+        old
+    }
+
+    /// 选出一个读取者不会访问的槽位
+    /// 只有两个槽位并且最新值还没有被读取时，先把最新值撤回到读取者占用的槽位，再覆盖原来的最新值
+    #[inline]
+    fn reserve_idx(&self) -> usize {
+        let mut idx = self.idx.load(Ordering::Acquire);
+        loop {
+            let (set_idx, get_idx) = (Self::set_idx(idx), Self::get_idx(idx));
+            if let Some(next) = Self::free_idx(set_idx, get_idx) {
+                return next;
+            }
+            match self.idx.compare_exchange(idx, Self::pack(get_idx, get_idx), Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return set_idx,
+                // 读取者抢先占用了最新值，原来占用的槽位空了出来
+                Err(cur) => idx = cur,
+            }
+        }
+    }
+
+    /// 发布最新值所在的槽位，保留读取者占用的槽位
+    #[inline]
+    fn publish(&self, next_idx: usize) {
+        let _ = self.idx.fetch_update(Ordering::AcqRel, Ordering::Acquire, |idx| Some(Self::pack(next_idx, Self::get_idx(idx))));
+    }
+
+    /// 通过共享引用设置缓冲区数据
+    ///
+    /// # Safety
+    /// 同一时刻只能有一个写入者，并且读取者没有在访问该槽位
+    #[inline]
+    pub(crate) unsafe fn set_value_unchecked(&self, idx: usize, value: T) -> S::Slot {
+        self.data[idx].with_mut(|slot| std::mem::replace(&mut *slot, S::fill(value)))
+    }
+
+    /// 读取者占用最新值所在的槽位，返回槽位索引
+    #[inline]
+    fn claim_last(&self) -> Result<usize, Error> {
+        let mut idx = self.idx.load(Ordering::Acquire);
+        loop {
+            let set_idx = Self::set_idx(idx);
+            if set_idx == Self::get_idx(idx) {
+                return match self.check_changed() {
+                    Some(Err(err)) => Err(err),
+                    _ => Err(Error::Empty),
+                };
+            }
+            // 这里注意必须先占坑，这样写入线程就会跳过坑，读取最新值的索引和占坑必须是同一次原子操作，
+            // 否则写入者可能在两者之间重新写入这个槽位
+            match self.idx.compare_exchange(idx, Self::pack(set_idx, set_idx), Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Ok(set_idx),
+                Err(cur) => idx = cur,
+            }
+        }
+    }
+
+    /// 通过共享引用获取最新的数据
+    ///
+    /// # Safety
+    /// 同一时刻只能有一个读取者
+    #[inline]
+    pub(crate) unsafe fn get_last_unchecked(&self) -> Result<T, Error> {
+        let idx = self.claim_last()?;
+        self.data[idx].with_mut(|slot| S::take_value(&mut *slot)).ok_or(Error::Empty)
+    }
+
+    /// 通过共享引用访问缓冲区数据
+    ///
+    /// # Safety
+    /// 返回的引用存活期间，该槽位不能被修改
+    #[inline]
+    pub(crate) unsafe fn at_unchecked(&self, idx: usize) -> &S::Slot {
+        self.data[idx].with(|slot| &*slot)
+    }
+
+    /// 通过共享引用可变访问缓冲区数据
+    ///
+    /// # Safety
+    /// 返回的引用存活期间，该槽位不能被其他任何引用访问
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) unsafe fn at_mut_unchecked(&self, idx: usize) -> &mut S::Slot {
+        self.data[idx].with_mut(|slot| &mut *slot)
+    }
+
+    /// 通过共享引用清除整个缓冲区
+    ///
+    /// # Safety
+    /// 调用期间读取者和写入者都不能访问缓冲区
+    #[inline]
+    pub(crate) unsafe fn clear_unchecked(&self) {
+        self.idx.store(0, Ordering::Release);
+        for i in self.data.iter() {
+            i.with_mut(|slot| *slot = S::vacant());
+        }
+    }
+
+    /// 撤回还没有被读取的最新值，读取者占用的槽位不变
+    #[inline]
+    fn discard_last(&self) {
+        let _ = self.idx.fetch_update(Ordering::AcqRel, Ordering::Acquire, |idx| Some(Self::pack(Self::get_idx(idx), Self::get_idx(idx))));
+    }
+}
+
+impl<T, const SIZE: usize> LockFreeValue<T, SIZE, Optional> {
+    /// 清除整个缓冲区
+    #[inline]
+    pub fn clear(&mut self) {
+        unsafe { self.clear_unchecked() }
+    }
+}
+
+impl<T: Default, const SIZE: usize> LockFreeValue<T, SIZE, Defaulted> {
+    /// 获取最新的数据
+    #[inline]
+    pub fn get_last_ref(&mut self) -> Result<&T, Error> {
+        unsafe { self.get_last_ref_unchecked() }
+    }
+
+    /// 获取最新的数据
+    #[inline]
+    pub fn get_last_mut(&mut self) -> Result<&mut T, Error> {
+        unsafe { self.get_last_mut_unchecked() }
+    }
+
+    /// 清除整个缓冲区
+    #[inline]
+    pub fn clear(&mut self) {
+        self.idx.store(0, Ordering::Release);
+    }
+
+    /// 通过共享引用获取最新的数据
+    ///
+    /// # Safety
+    /// 同一时刻只能有一个读取者，返回的引用存活期间不能再次占用槽位
+    #[inline]
+    pub(crate) unsafe fn get_last_ref_unchecked(&self) -> Result<&T, Error> {
+        let idx = self.claim_last()?;
+        Ok(self.data[idx].with(|slot| &*slot))
+    }
+
+    /// 通过共享引用获取最新的数据
+    ///
+    /// # Safety
+    /// 同一时刻只能有一个读取者，返回的引用存活期间不能再次占用槽位
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) unsafe fn get_last_mut_unchecked(&self) -> Result<&mut T, Error> {
+        let idx = self.claim_last()?;
+        Ok(self.data[idx].with_mut(|slot| &mut *slot))
+    }
+}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> Index<usize> for LockFreeValue<T, SIZE, S> {
+    type Output = S::Slot;
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.at(index)
+    }
+}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> IndexMut<usize> for LockFreeValue<T, SIZE, S> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.at_mut(index)
+    }
+}
+
+/// 读取者只有一个，`get_last` 会修改get_idx并取走槽位中的数据，不能通过 &ValueReader 在线程之间共享，所以不实现 Sync
+pub struct ValueReader<T, const SIZE: usize, S: ValueStorage<T> = Optional> {
+    inner: Arc<LockFreeValue<T, SIZE, S>>,
+    /// 不自动实现 Send 和 Sync，由下面的 unsafe impl 显式给出
+    _marker: PhantomData<*mut ()>,
+}
+
+/// 读取者取出的 T 来自写入者所在的线程，释放时也可能在这里析构剩余的数据，所以要求 T: Send
+unsafe impl<T: Send, const SIZE: usize, S: ValueStorage<T>> Send for ValueReader<T, SIZE, S> {}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> ValueReader<T, SIZE, S> {
+    /// 缓冲区大小
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    /// 最新值是否已经发生变化
+    #[inline]
+    pub fn changed(&self) -> bool {
+        self.inner.changed()
+    }
+
+    /// 最新值是否没有发生变化
+    #[inline]
+    pub fn unchanged(&self) -> bool {
+        self.inner.unchanged()
+    }
+
+    /// 写入者是否已经被释放
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        !self.inner.writer_alive.load(Ordering::Acquire)
+    }
+
+    /// 阻塞当前线程，直到最新值发生变化，写入者已经被释放并且没有新值时返回 `Error::Disconnected`
+    #[inline]
+    pub fn wait_changed(&mut self) -> Result<(), Error> {
+        let inner = &self.inner;
+        signal::block_on(&inner.signal, || inner.check_changed())
+    }
+
+    /// 阻塞当前线程，直到最新值发生变化，最多等待 timeout，超时返回 `Error::Timeout`
+    #[inline]
+    pub fn wait_changed_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+        self.wait_changed_until(Instant::now().checked_add(timeout))
+    }
+
+    /// 阻塞当前线程，直到最新值发生变化，最多等待到 deadline，超时返回 `Error::Timeout`
+    #[inline]
+    pub fn wait_changed_deadline(&mut self, deadline: Instant) -> Result<(), Error> {
+        self.wait_changed_until(Some(deadline))
+    }
+
+    fn wait_changed_until(&mut self, deadline: Option<Instant>) -> Result<(), Error> {
+        let inner = &self.inner;
+        signal::wait(&inner.signal, deadline, || inner.check_changed()).unwrap_or(Err(Error::Timeout))
+    }
+
+    /// 异步等待最新值发生变化，没有变化时登记当前任务的 Waker，写入者写入新值后唤醒
+    #[inline]
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let inner = &self.inner;
+        signal::poll(&inner.signal, cx, || inner.check_changed())
+    }
+
+    /// 异步等待最新值发生变化，写入者已经被释放并且没有新值时返回 `Error::Disconnected`
+    pub async fn wait_changed_async(&mut self) -> Result<(), Error> {
+        std::future::poll_fn(|cx| self.poll_changed(cx)).await
+    }
+
+    /// 最新值所在的槽位索引
+    #[inline]
+    pub fn last_idx(&self) -> usize {
+        LockFreeValue::<T, SIZE, S>::set_idx(self.inner.idx.load(Ordering::Acquire))
+    }
+
+    /// 获取最新的数据，没有新值时返回 `Error::Empty`，
+    /// 写入者已经被释放并且没有新值时返回 `Error::Disconnected`
+    #[inline]
+    pub fn get_last(&mut self) -> Result<T, Error> {
+        // Reader 没有实现 Clone，且读取需要 &mut self，所以同一时刻只会有一个读取者
+        unsafe { self.inner.get_last_unchecked() }
+    }
+
+    /// 获取缓冲区数据
+    ///
+    /// # Safety
+    /// 返回的引用存活期间，写入者不能写入该槽位
+    #[inline]
+    pub unsafe fn at(&self, idx: usize) -> &S::Slot {
+        self.inner.at_unchecked(idx)
+    }
+}
+
+impl<T: Default, const SIZE: usize> ValueReader<T, SIZE, Defaulted> {
+    // 返回的引用借用了 self，存活期间无法再次占用其他槽位
+    #[inline]
+    pub fn get_last_ref(&mut self) -> Result<&T, Error> {
+        unsafe { self.inner.get_last_ref_unchecked() }
+    }
+
+    #[inline]
+    pub fn get_last_mut(&mut self) -> Result<&mut T, Error> {
+        unsafe { self.inner.get_last_mut_unchecked() }
+    }
+}
+
+/// 写入者只有一个，不能通过 &ValueWriter 在线程之间共享，所以不实现 Sync
+pub struct ValueWriter<T, const SIZE: usize, S: ValueStorage<T> = Optional> {
+    inner: Arc<LockFreeValue<T, SIZE, S>>,
+    /// 不自动实现 Send 和 Sync，由下面的 unsafe impl 显式给出
+    _marker: PhantomData<*mut ()>,
+}
+
+/// 写入的 T 由读取者在另一个线程取出，所以要求 T: Send
+unsafe impl<T: Send, const SIZE: usize, S: ValueStorage<T>> Send for ValueWriter<T, SIZE, S> {}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> ValueWriter<T, SIZE, S> {
+    /// 缓冲区大小
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+    /// 获取下一个位置的索引
+    #[inline]
+    pub fn next_idx(&self) -> usize {
+        self.inner.next_idx()
+    }
+    /// 安全地获取下一个位置的索引，这将检查下一个索引是否已经转过一圈
+    #[inline]
+    pub fn next_idx_safe(&self) -> usize {
+        self.inner.next_idx_safe()
+    }
+
+    /// 放入最新值，返回被替换掉的槽位内容，读取者已经被释放时通过 `SendError` 将值原样返回
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<S::Slot, SendError<T>> {
+        if self.is_disconnected() {
+            return Err(SendError::Disconnected(value));
+        }
+        // Writer 没有实现 Clone，且写入需要 &mut self，所以同一时刻只会有一个写入者
+        let old = unsafe { self.inner.push_unchecked(value) };
+        self.inner.signal.notify();
+        Ok(old)
+    }
+
+    /// 设置缓冲区数据
+    ///
+    /// # Safety
+    /// 读取者不能正在访问该槽位，通常 idx 应该来自 `next_idx_safe`
+    #[inline]
+    pub unsafe fn set_value(&mut self, idx: usize, value: T) -> S::Slot {
+        self.inner.set_value_unchecked(idx, value)
+    }
+
+    /// 设置下一个索引，这里使用 mut 限制，如果不限制 意味着 如果被Arc包裹，那么会有多个所有者修改数据，这是不安全的
+    #[inline]
+    pub fn set_next_idx(&mut self, next_idx: usize) {
+        self.inner.publish(next_idx);
+        self.inner.signal.notify();
+    }
+
+    /// 最新值是否已经发生变化
+    #[inline]
+    pub fn changed(&self) -> bool {
+        self.inner.changed()
+    }
+
+    /// 最新值是否没有发生变化
+    #[inline]
+    pub fn unchanged(&self) -> bool {
+        self.inner.unchanged()
+    }
+
+    /// 读取者是否已经被释放
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        !self.inner.reader_alive.load(Ordering::Acquire)
+    }
+
+    /// 获取缓冲区数据
+    ///
+    /// # Safety
+    /// 返回的引用存活期间，读取者不能取走该槽位的数据
+    #[inline]
+    pub unsafe fn at(&self, idx: usize) -> &S::Slot {
+        self.inner.at_unchecked(idx)
+    }
+
+    /// 获取缓冲区数据可变
+    ///
+    /// # Safety
+    /// 返回的引用存活期间，读取者不能访问该槽位
+    #[inline]
+    pub unsafe fn at_mut(&mut self, idx: usize) -> &mut S::Slot {
+        self.inner.at_mut_unchecked(idx)
+    }
+}
+
+impl<T, const SIZE: usize> ValueWriter<T, SIZE, Optional> {
+    /// 清除整个缓冲区 这里使用 mut 限制，如果不限制 意味着 如果被Arc包裹，那么会有多个所有者修改数据，这是不安全的
+    ///
+    /// # Safety
+    /// 调用期间读取者不能访问缓冲区
+    #[inline]
+    pub unsafe fn clear(&mut self) {
+        self.inner.clear_unchecked()
+    }
+}
+
+impl<T: Default, const SIZE: usize> ValueWriter<T, SIZE, Defaulted> {
+    /// 清除整个缓冲区 这里使用 mut 限制，如果不限制 意味着 如果被Arc包裹，那么会有多个所有者修改数据，这是不安全的
+    /// 只撤回还没有被读取的最新值，槽位中的数据保留，读取者占用的槽位不受影响
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.discard_last();
+    }
+}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> Drop for ValueReader<T, SIZE, S> {
+    fn drop(&mut self) {
+        self.inner.reader_alive.store(false, Ordering::Release);
+    }
+}
+
+impl<T, const SIZE: usize, S: ValueStorage<T>> Drop for ValueWriter<T, SIZE, S> {
+    fn drop(&mut self) {
+        self.inner.writer_alive.store(false, Ordering::Release);
+        self.inner.signal.notify();
+    }
+}
+
+/// 创建写入者和读取者，SIZE 至少为2，否则无法通过编译：
+/// ```compile_fail
+/// let (writer, reader) = lockfree::value::make_value::<u32, 1>();
+/// ```
+pub fn make_value<T, const SIZE: usize>() -> (ValueWriter<T, SIZE>, ValueReader<T, SIZE>)
+{
+    make_value_with()
+}
+
+/// 同 `make_value`，通过 S 指定槽位的存储策略
+/// ```
+/// use lockfree::storage::Defaulted;
+///
+/// let (mut writer, mut reader) = lockfree::value::make_value_with::<u32, 4, Defaulted>();
+/// assert_eq!(writer.push(7).unwrap(), 0);
+/// assert_eq!(*reader.get_last_ref().unwrap(), 7);
+/// ```
+pub fn make_value_with<T, const SIZE: usize, S: ValueStorage<T>>() -> (ValueWriter<T, SIZE, S>, ValueReader<T, SIZE, S>)
+{
+    let ring = LockFreeValue::new_arc();
+    let writer = ValueWriter {
+        inner: ring.clone(),
+        _marker: PhantomData,
+    };
+    let reader = ValueReader {
+        inner: ring,
+        _marker: PhantomData,
+    };
+    (writer, reader)
+}
@@ -0,0 +1,143 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use lockfree::error::{Error, SendError};
+use lockfree::queue::ringbuffer;
+use lockfree::value::make_value;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// 不依赖任何运行时的最小执行器
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn async_send_recv() {
+    const COUNT: u64 = 10_000;
+    let (mut tx, mut rx) = ringbuffer::<u64, 4>();
+
+    let producer = thread::spawn(move || {
+        block_on(async {
+            for i in 0..COUNT {
+                tx.send_async(i).await.unwrap();
+            }
+        })
+    });
+
+    block_on(async {
+        for i in 0..COUNT {
+            assert_eq!(rx.recv_async().await.unwrap(), i);
+        }
+        assert!(matches!(rx.recv_async().await, Err(Error::Disconnected)));
+    });
+    producer.join().unwrap();
+}
+
+#[test]
+fn async_send_disconnected() {
+    let (mut tx, rx) = ringbuffer::<u32, 2>();
+    drop(rx);
+    assert_eq!(block_on(tx.send_async(1)), Err(SendError::Disconnected(1)));
+
+    let (mut tx, _rx) = ringbuffer::<u32, 2>();
+    tx.close();
+    assert_eq!(block_on(tx.send_async(2)), Err(SendError::Closed(2)));
+}
+
+#[test]
+fn async_wait_changed() {
+    let (mut writer, mut reader) = make_value::<u32, 4>();
+
+    let producer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        writer.push(3).unwrap();
+    });
+
+    block_on(async {
+        reader.wait_changed_async().await.unwrap();
+        assert_eq!(reader.get_last().unwrap(), 3);
+        assert!(matches!(reader.wait_changed_async().await, Err(Error::Disconnected)));
+    });
+    producer.join().unwrap();
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn stream_and_sink() {
+    use futures_core::Stream;
+    use futures_sink::Sink;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+
+    let producer = thread::spawn(move || {
+        block_on(async {
+            for i in 0..100 {
+                poll_fn(|cx| Pin::new(&mut tx).poll_ready(cx)).await.unwrap();
+                Pin::new(&mut tx).start_send(i).unwrap();
+            }
+        })
+    });
+
+    let received = block_on(async {
+        let mut received = Vec::new();
+        while let Some(v) = poll_fn(|cx| Pin::new(&mut rx).poll_next(cx)).await {
+            received.push(v);
+        }
+        received
+    });
+    producer.join().unwrap();
+    assert_eq!(received, (0..100).collect::<Vec<_>>());
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn sink_close() {
+    use futures_sink::Sink;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    // 各个队列的 poll_close 都会关闭写入者，读取者取完数据之后收到 Disconnected
+    let (mut tx, mut rx) = lockfree::lockfree_mpsc::ringbuffer::<u32, 4>();
+    let mut tx2 = tx.clone();
+    Pin::new(&mut tx).start_send(1).unwrap();
+    block_on(poll_fn(|cx| Pin::new(&mut tx).poll_close(cx))).unwrap();
+    assert!(tx.is_closed());
+    assert_eq!(Pin::new(&mut tx).start_send(2), Err(Error::Closed));
+    block_on(poll_fn(|cx| Pin::new(&mut tx2).poll_close(cx))).unwrap();
+    assert_eq!(rx.recv().unwrap(), 1);
+    assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+
+    let (mut tx, mut rx) = lockfree::lockfree_spmc::ringbuffer::<u32, 4>();
+    block_on(poll_fn(|cx| Pin::new(&mut tx).poll_close(cx))).unwrap();
+    assert_eq!(Pin::new(&mut tx).start_send(1), Err(Error::Closed));
+    assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+
+    let (mut tx, mut rx) = lockfree::lockfree_heap::ringbuffer::<u32>(4).unwrap();
+    block_on(poll_fn(|cx| Pin::new(&mut tx).poll_close(cx))).unwrap();
+    assert_eq!(Pin::new(&mut tx).start_send(1), Err(Error::Closed));
+    assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+}
@@ -0,0 +1,117 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::thread;
+
+use lockfree::lockfree_broadcast::ringbuffer;
+use lockfree::error::{Error, TrySendError};
+
+#[test]
+fn broadcast_gated_by_slowest() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    let mut fast = tx.subscribe();
+    assert_eq!(tx.subscriber_count(), 2);
+    for i in 0..4 {
+        tx.push(i).unwrap();
+    }
+    for i in 0..4 {
+        assert_eq!(fast.pop().unwrap(), i);
+    }
+    assert!(matches!(fast.pop(), Err(Error::Empty)));
+    // rx 还没有读取，写入者不能覆盖
+    assert!(matches!(tx.try_push(4), Err(TrySendError::Full(4))));
+    assert_eq!(rx.pop().unwrap(), 0);
+    tx.push(4).unwrap();
+    assert_eq!(rx.len(), 4);
+    assert_eq!(fast.pop().unwrap(), 4);
+
+    // 新订阅者可以从当前位置或者最早保留的数据开始读取
+    let mut late = tx.subscribe();
+    assert!(late.is_empty());
+    let mut replay = tx.subscribe_from_oldest();
+    assert_eq!(replay.pop().unwrap(), 1);
+
+    drop(rx);
+    drop(replay);
+    tx.push(5).unwrap();
+    assert_eq!(late.pop().unwrap(), 5);
+    drop(tx);
+    assert!(matches!(late.pop(), Err(Error::Disconnected)));
+    assert_eq!(fast.recv().unwrap(), 5);
+    assert!(matches!(fast.recv(), Err(Error::Disconnected)));
+}
+
+#[test]
+fn broadcast_replay_with_stale_gate() {
+    let (mut tx, rx) = ringbuffer::<u32, 4>();
+    drop(rx);
+    let mut fast = tx.subscribe();
+    for i in 0..4 {
+        tx.push(i).unwrap();
+    }
+    for i in 0..4 {
+        assert_eq!(fast.pop().unwrap(), i);
+    }
+    // 写入者按 fast 重新计算 gate，gate 追上了 fast，但 1..=4 仍然保留在环中
+    tx.push(4).unwrap();
+    assert_eq!(fast.pop().unwrap(), 4);
+
+    // 写入者可能正在覆盖最早的数据 1，回放从 2 开始
+    let mut replay = tx.subscribe_from_oldest();
+    assert_eq!(replay.len(), 3);
+    tx.push(5).unwrap();
+    // 降低后的 gate 阻止写入者覆盖回放还没读取的数据
+    assert!(matches!(tx.try_push(6), Err(TrySendError::Full(6))));
+    for i in 2..6 {
+        assert_eq!(replay.pop().unwrap(), i);
+    }
+    tx.push(6).unwrap();
+    assert_eq!(replay.pop().unwrap(), 6);
+    assert_eq!(fast.pop().unwrap(), 5);
+    assert_eq!(fast.pop().unwrap(), 6);
+
+    // 写入者被最慢的订阅者挡住时不会写入，最早的数据可以完整回放
+    for i in 7..11 {
+        tx.push(i).unwrap();
+    }
+    assert!(matches!(tx.try_push(11), Err(TrySendError::Full(11))));
+    let mut replay = tx.subscribe_from_oldest();
+    assert_eq!(replay.len(), 4);
+    assert_eq!(replay.pop().unwrap(), 7);
+}
+
+#[test]
+fn broadcast_every_subscriber_sees_everything() {
+    const SUBSCRIBERS: usize = 4;
+    const COUNT: u64 = 20_000;
+    let (mut tx, rx) = ringbuffer::<u64, 8>();
+
+    let subscribers: Vec<_> = (0..SUBSCRIBERS)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut expected = 0;
+                loop {
+                    match rx.recv() {
+                        Ok(v) => {
+                            assert_eq!(v, expected);
+                            expected += 1;
+                        }
+                        Err(Error::Disconnected) => return expected,
+                        Err(e) => panic!("unexpected {:?}", e),
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(rx);
+
+    for i in 0..COUNT {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+
+    for s in subscribers {
+        assert_eq!(s.join().unwrap(), COUNT);
+    }
+}
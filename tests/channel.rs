@@ -0,0 +1,85 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::thread;
+use std::time::Duration;
+
+use lockfree::channel::{Builder, Overflow, WaitStrategy};
+use lockfree::error::{Error, TrySendError};
+
+#[test]
+fn overflow_policies() {
+    let (mut tx, mut rx) = Builder::<u32>::new().overflow(Overflow::Reject).build();
+    for i in 0..4 {
+        tx.send(i).unwrap();
+    }
+    assert!(matches!(tx.send(4), Err(TrySendError::Full(4))));
+    assert_eq!(rx.recv().unwrap(), 0);
+
+    let (mut tx, mut rx) = Builder::<u32>::new().overflow(Overflow::DropNewest).build();
+    for i in 0..6 {
+        tx.send(i).unwrap();
+    }
+    for i in 0..4 {
+        assert_eq!(rx.recv().unwrap(), i);
+    }
+    assert!(matches!(rx.try_recv(), Err(Error::Empty)));
+
+    let (mut tx, mut rx) = Builder::<u32>::new().overflow(Overflow::DropOldest).build();
+    for i in 0..6 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(rx.recv().unwrap(), 2);
+    assert_eq!(rx.missed(), 2);
+
+    drop(rx);
+    assert!(matches!(tx.send(6), Err(TrySendError::Disconnected(6))));
+
+    // Block 的 send 不会返回 Full，读取者被释放时即使队列已满也返回 Disconnected
+    for wait in [WaitStrategy::Park, WaitStrategy::SpinThenYield] {
+        let (mut tx, rx) = Builder::<u32>::new().wait_strategy(wait).build();
+        for i in 0..4 {
+            tx.send(i).unwrap();
+        }
+        drop(rx);
+        assert!(matches!(tx.send(4), Err(TrySendError::Disconnected(4))));
+    }
+}
+
+#[test]
+fn wait_strategies() {
+    // Spin 在单核机器上只能等到时间片用完才会切换，数据量不宜太大
+    const COUNT: u64 = 1_000;
+    for wait in [WaitStrategy::Spin, WaitStrategy::SpinThenYield, WaitStrategy::Park, WaitStrategy::Backoff] {
+        let (mut tx, mut rx) = Builder::<u64>::new()
+            .capacity::<8>()
+            .wait_strategy(wait)
+            .build();
+        assert_eq!(tx.capacity(), 8);
+        assert!(matches!(rx.recv_timeout(Duration::from_millis(5)), Err(Error::Timeout)));
+
+        let producer = thread::spawn(move || {
+            for i in 0..COUNT {
+                tx.send(i).unwrap();
+            }
+        });
+        for i in 0..COUNT {
+            assert_eq!(rx.recv().unwrap(), i);
+        }
+        assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+        producer.join().unwrap();
+    }
+}
+
+#[test]
+fn builder_reused_without_clone_bound() {
+    struct Frame(Vec<u8>);
+
+    let builder = Builder::<Frame>::new().capacity::<8>().overflow(Overflow::Reject);
+    let (mut tx1, mut rx1) = builder.build();
+    let (_tx2, rx2) = builder.build();
+    assert!(format!("{builder:?}").contains("capacity: 8"));
+    tx1.send(Frame(vec![1])).ok().unwrap();
+    assert_eq!(rx1.recv().ok().unwrap().0, [1]);
+    assert_eq!(rx2.capacity(), 8);
+}
@@ -0,0 +1,40 @@
+//! 句柄和守卫的 Send/Sync 约束，以及 trybuild 检查的误用，错误输出以默认特性为准，以 `--cfg loom` 编译时没有 `WriteChunk` 和 `ReadChunk`
+#![cfg(not(loom))]
+
+use std::rc::Rc;
+
+use lockfree::queue::{Peek, ReadChunk, ReadGuard, RingBufferReceiver, RingBufferSender, WriteChunk, WriteGuard};
+use lockfree::storage::Defaulted;
+use lockfree::value::{ValueReader, ValueWriter};
+
+fn assert_send<T: Send>() {}
+
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn handles_are_send() {
+    assert_send::<RingBufferSender<String, 4>>();
+    assert_send::<RingBufferReceiver<String, 4>>();
+    assert_send::<RingBufferSender<String, 4, Defaulted>>();
+    assert_send::<ValueWriter<Vec<u8>, 4>>();
+    assert_send::<ValueReader<Vec<u8>, 4, Defaulted>>();
+    // T 不是 Send 时句柄不能跨线程，但在单线程中照常使用
+    let (mut tx, mut rx) = lockfree::queue::ringbuffer::<Rc<u32>, 4>();
+    tx.push(Rc::new(1)).unwrap();
+    assert_eq!(*rx.pop().unwrap(), 1);
+}
+
+#[test]
+fn guards_are_send_and_sync() {
+    assert_send::<WriteGuard<'static, String, 4>>();
+    assert_sync::<ReadGuard<'static, String, 4, Defaulted>>();
+    assert_sync::<Peek<'static, String, 4>>();
+    assert_send::<WriteChunk<'static, u8, 4, Defaulted>>();
+    assert_sync::<ReadChunk<'static, u8, 4>>();
+}
+
+#[test]
+fn misuse_is_rejected() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+}
@@ -0,0 +1,15 @@
+use std::cell::Cell;
+
+use lockfree::queue::{Peek, ReadChunk, ReadGuard, WriteChunk, WriteGuard};
+use lockfree::storage::Defaulted;
+
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    // Cell 可以跨线程移动，但不能共享，通过守卫拿到的 &Cell 也不能在线程之间共享
+    assert_sync::<WriteGuard<'static, Cell<u32>, 4, Defaulted>>();
+    assert_sync::<ReadGuard<'static, Cell<u32>, 4>>();
+    assert_sync::<Peek<'static, Cell<u32>, 4>>();
+    assert_sync::<WriteChunk<'static, Cell<u32>, 4>>();
+    assert_sync::<ReadChunk<'static, Cell<u32>, 4>>();
+}
@@ -0,0 +1,11 @@
+use lockfree::queue::{RingBufferReceiver, RingBufferSender};
+use lockfree::value::{ValueReader, ValueWriter};
+
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    assert_sync::<RingBufferSender<u32, 4>>();
+    assert_sync::<RingBufferReceiver<u32, 4>>();
+    assert_sync::<ValueWriter<u32, 4>>();
+    assert_sync::<ValueReader<u32, 4>>();
+}
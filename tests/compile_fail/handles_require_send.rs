@@ -0,0 +1,13 @@
+use std::rc::Rc;
+
+use lockfree::queue::{RingBufferReceiver, RingBufferSender};
+use lockfree::value::{ValueReader, ValueWriter};
+
+fn assert_send<T: Send>() {}
+
+fn main() {
+    assert_send::<RingBufferSender<Rc<u32>, 4>>();
+    assert_send::<RingBufferReceiver<Rc<u32>, 4>>();
+    assert_send::<ValueWriter<Rc<u32>, 4>>();
+    assert_send::<ValueReader<Rc<u32>, 4>>();
+}
@@ -0,0 +1,11 @@
+use std::rc::Rc;
+use std::thread;
+
+fn main() {
+    let (mut tx, rx) = lockfree::queue::ringbuffer::<Rc<u32>, 4>();
+    // 写入者被移动到另一个线程，队列中的 Rc 会和留在这里的克隆共享非原子的引用计数
+    let local = Rc::new(1);
+    tx.push(local.clone()).unwrap();
+    thread::spawn(move || drop(tx));
+    drop((rx, local));
+}
@@ -0,0 +1,11 @@
+use std::thread;
+
+fn main() {
+    let (mut writer, reader) = lockfree::value::make_value::<u32, 4>();
+    writer.push(1);
+    // 读取者不能被两个线程同时使用
+    thread::scope(|s| {
+        s.spawn(|| reader.changed());
+        s.spawn(|| reader.changed());
+    });
+}
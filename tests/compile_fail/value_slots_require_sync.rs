@@ -0,0 +1,14 @@
+use std::cell::Cell;
+use std::thread;
+
+use lockfree::value::LockFreeValue;
+use lockfree::storage::Defaulted;
+
+fn main() {
+    // 通过 &LockFreeValue 拿到的 &Cell 不能在线程之间共享，否则两个线程可以同时修改同一个槽位
+    let v = LockFreeValue::<Cell<u64>, 2, Defaulted>::new();
+    thread::scope(|s| {
+        s.spawn(|| v[1].set(1));
+        s.spawn(|| v[1].set(2));
+    });
+}
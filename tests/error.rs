@@ -0,0 +1,53 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use lockfree::error::{Error, SendError, SendTimeoutError, TrySendError};
+use lockfree::queue::ringbuffer;
+
+#[test]
+fn error_kinds_and_conversions() {
+    assert!(Error::Empty.is_empty() && !Error::Empty.is_full());
+    assert!(Error::Closed.is_closed() && Error::Timeout.is_timeout() && Error::Disconnected.is_disconnected());
+    assert_eq!(Error::Full.to_string(), "ring buffer is full");
+    assert_eq!(Error::Closed.to_string(), "ring buffer is closed");
+    let boxed: Box<dyn std::error::Error> = Box::new(Error::Empty);
+    assert!(boxed.source().is_none());
+
+    let err = TrySendError::Closed(String::from("a"));
+    assert!(err.is_closed() && !err.is_full());
+    assert_eq!(err.kind(), Error::Closed);
+    assert_eq!(err.to_string(), "sending on a closed ring buffer");
+    assert_eq!(format!("{:?}", err), "Closed(..)");
+    assert_eq!(Error::from(err), Error::Closed);
+
+    // 带上值转换回写入错误，值不会丢失
+    assert_eq!(Error::Full.with_value(1), Ok(TrySendError::Full(1)));
+    assert_eq!(Error::Empty.with_value(2), Err((Error::Empty, 2)));
+    assert_eq!(TrySendError::from(SendError::Disconnected(3)), TrySendError::Disconnected(3));
+    assert_eq!(SendTimeoutError::from(SendError::Disconnected(4)).into_inner(), 4);
+    // 关闭和断开在转换之后仍然可以区分
+    let err = SendError::Closed(6);
+    assert!(err.is_closed() && !err.is_disconnected());
+    assert_eq!(err.to_string(), "sending on a closed ring buffer");
+    assert_eq!(TrySendError::from(err), TrySendError::Closed(6));
+    assert_eq!(SendTimeoutError::from(err), SendTimeoutError::Closed(6));
+    assert_eq!(Error::from(err), Error::Closed);
+    assert_eq!(Error::from(SendError::Disconnected(7)), Error::Disconnected);
+    assert_eq!(SendTimeoutError::Timeout(5).kind(), Error::Timeout);
+}
+
+#[test]
+fn closed_sender() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    tx.push(1).unwrap();
+    tx.close();
+    assert!(tx.is_closed() && !tx.is_disconnected());
+    assert_eq!(tx.try_push(2), Err(TrySendError::Closed(2)));
+    assert!(matches!(tx.reserve(), Err(Error::Closed)));
+    assert_eq!(tx.send_timeout(3, std::time::Duration::ZERO), Err(SendTimeoutError::Closed(3)));
+    assert_eq!(tx.send(4), Err(SendError::Closed(4)));
+
+    // 关闭之前写入的数据仍然可以取出，之后读取者收到 Disconnected
+    assert_eq!(rx.recv().unwrap(), 1);
+    assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+}
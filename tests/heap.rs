@@ -0,0 +1,66 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::thread;
+
+use lockfree::lockfree_heap::ringbuffer;
+use lockfree::error::{CapacityError, Error, TrySendError};
+
+#[test]
+fn heap_capacity_is_validated() {
+    assert_eq!(ringbuffer::<u32>(0).err(), Some(CapacityError(0)));
+    assert_eq!(ringbuffer::<u32>(usize::MAX).err(), Some(CapacityError(usize::MAX)));
+
+    let (tx, _rx) = ringbuffer::<u32>(3).unwrap();
+    assert_eq!((tx.capacity(), tx.size()), (4, 4));
+    // 向上取整为2的幂
+    let (tx, _rx) = ringbuffer::<u32>(1000).unwrap();
+    assert_eq!((tx.capacity(), tx.size()), (1024, 1024));
+}
+
+#[test]
+fn heap_spsc() {
+    const COUNT: u64 = 100_000;
+    let (mut tx, mut rx) = ringbuffer::<u64>(5).unwrap();
+    for i in 0..8 {
+        tx.push(i).unwrap();
+    }
+    assert!(matches!(tx.try_push(8), Err(TrySendError::Full(8))));
+    for i in 0..8 {
+        assert_eq!(rx.pop().unwrap(), i);
+    }
+
+    let producer = thread::spawn(move || {
+        for i in 0..COUNT {
+            tx.send(i).unwrap();
+        }
+    });
+    for i in 0..COUNT {
+        assert_eq!(rx.recv().unwrap(), i);
+    }
+    assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+    producer.join().unwrap();
+}
+
+#[test]
+fn heap_shares_queue_api() {
+    let (mut tx, mut rx) = ringbuffer::<u32>(2).unwrap();
+    tx.reserve().unwrap().write(1);
+    assert!(matches!(rx.try_pop(), Err(Error::Empty)));
+    let mut guard = tx.reserve().unwrap();
+    guard.write(2);
+    guard.commit();
+    assert_eq!(tx.push_slice(&[3, 4]), 1);
+    assert_eq!(rx.pop_guard().unwrap().commit(), 2);
+    tx.close();
+    assert!(matches!(tx.try_push(5), Err(TrySendError::Closed(5))));
+    assert_eq!(rx.pop().unwrap(), 3);
+    assert!(matches!(rx.pop(), Err(Error::Disconnected)));
+
+    let (mut tx, mut rx) = lockfree::queue::ringbuffer_overwrite_with_capacity::<u32>(3).unwrap();
+    for i in 0..6 {
+        tx.push(i).unwrap();
+    }
+    assert_eq!(rx.pop().unwrap(), 2);
+    assert_eq!(rx.missed(), 2);
+}
@@ -0,0 +1,69 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::thread;
+
+use lockfree::lockfree_mpmc;
+use lockfree::queue::{ringbuffer, ringbuffer_with};
+use lockfree::storage::Defaulted;
+use lockfree::value::{make_value, make_value_with};
+
+/// 远小于缓冲区本身的栈，缓冲区只要在栈上出现一次就会溢出
+const SMALL_STACK: usize = 256 * 1024;
+
+fn on_small_stack(f: impl FnOnce() + Send + 'static) {
+    thread::Builder::new()
+        .stack_size(SMALL_STACK)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+struct Frame([u8; 4096]);
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame([0; 4096])
+    }
+}
+
+#[test]
+fn large_ringbuffer_on_small_stack() {
+    on_small_stack(|| {
+        // 1024 * 4KB = 4MB
+        let (mut tx, mut rx) = ringbuffer::<[u8; 4096], 1024>();
+        tx.try_push([7; 4096]).unwrap();
+        assert_eq!(rx.try_pop().unwrap()[4095], 7);
+
+        let (mut tx, mut rx) = ringbuffer_with::<Frame, 1024, Defaulted>();
+        let mut frame = Frame::default();
+        frame.0[0] = 1;
+        tx.try_push(frame).unwrap();
+        assert_eq!(rx.try_pop().unwrap().0[0], 1);
+    });
+}
+
+#[test]
+fn large_value_on_small_stack() {
+    on_small_stack(|| {
+        let (mut writer, mut reader) = make_value::<[u8; 4096], 1024>();
+        writer.push([3; 4096]).unwrap();
+        assert_eq!(reader.get_last().unwrap()[4095], 3);
+
+        let (mut writer, mut reader) = make_value_with::<Frame, 1024, Defaulted>();
+        let mut frame = Frame::default();
+        frame.0[1] = 2;
+        writer.push(frame).unwrap();
+        assert_eq!(reader.get_last().unwrap().0[1], 2);
+    });
+}
+
+#[test]
+fn large_mpmc_on_small_stack() {
+    on_small_stack(|| {
+        let queue = lockfree_mpmc::RingBuffer::<[u64; 64], 8192>::new_arc();
+        queue.push([9; 64]).unwrap();
+        assert_eq!(queue.pop().unwrap()[63], 9);
+    });
+}
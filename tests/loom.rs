@@ -0,0 +1,182 @@
+//! loom 模型测试，穷举 RingBuffer 和 LockFreeValue 读写两端所有可能的交错：
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+#![cfg(loom)]
+
+use loom::thread;
+
+use lockfree::error::{Error, TrySendError};
+use lockfree::queue::{ringbuffer, ringbuffer_overwrite};
+use lockfree::storage::Defaulted;
+use lockfree::value::{make_value, make_value_with};
+
+fn model(f: impl Fn() + Sync + Send + 'static) {
+    let mut builder = loom::model::Builder::new();
+    if builder.preemption_bound.is_none() {
+        builder.preemption_bound = Some(3);
+    }
+    builder.check(f);
+}
+
+#[test]
+fn queue_push_pop() {
+    model(|| {
+        let (mut tx, mut rx) = ringbuffer::<u32, 2>();
+        let producer = thread::spawn(move || {
+            for i in 1..=3 {
+                let mut value = i;
+                while let Err(TrySendError::Full(v)) = tx.try_push(value) {
+                    value = v;
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            match rx.try_pop() {
+                Ok(v) => received.push(v),
+                Err(Error::Empty) => thread::yield_now(),
+                Err(err) => panic!("unexpected {err:?}"),
+            }
+        }
+        producer.join().unwrap();
+        assert_eq!(received, [1, 2, 3]);
+        assert!(matches!(rx.try_pop(), Err(Error::Disconnected)));
+    });
+}
+
+#[test]
+fn queue_wrap_around() {
+    model(|| {
+        let (mut tx, mut rx) = ringbuffer::<String, 2>();
+        // 先推进一格，接下来的写入从最后一个槽位回绕到第一个槽位
+        tx.try_push("0".to_string()).unwrap();
+        assert_eq!(rx.try_pop().unwrap(), "0");
+
+        let producer = thread::spawn(move || {
+            tx.try_push("1".to_string()).unwrap();
+            let mut value = "2".to_string();
+            while let Err(TrySendError::Full(v)) = tx.try_push(value) {
+                value = v;
+                thread::yield_now();
+            }
+        });
+
+        let first = loop {
+            match rx.try_pop() {
+                Ok(v) => break v,
+                Err(_) => thread::yield_now(),
+            }
+        };
+        assert_eq!(first, "1");
+        producer.join().unwrap();
+        assert_eq!(rx.try_pop().unwrap(), "2");
+        assert!(matches!(rx.try_pop(), Err(Error::Disconnected)));
+    });
+}
+
+#[test]
+fn queue_overwrite() {
+    model(|| {
+        let (mut tx, mut rx) = ringbuffer_overwrite::<Box<u32>, 2>();
+        let producer = thread::spawn(move || {
+            for i in 1..=3 {
+                tx.try_push(Box::new(i)).unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        // missed 是每次取出之前被覆盖的个数，累加起来就是被覆盖的总数
+        let mut missed = 0;
+        if let Ok(v) = rx.try_pop() {
+            received.push(*v);
+            missed += rx.missed();
+        }
+        producer.join().unwrap();
+        while let Ok(v) = rx.try_pop() {
+            received.push(*v);
+            missed += rx.missed();
+        }
+        // 被覆盖的只能是最早的数据，留下的数据保持写入顺序，最后写入的数据一定能取到
+        assert!(received.windows(2).all(|w| w[0] < w[1]), "{received:?}");
+        assert_eq!(received.last(), Some(&3));
+        assert_eq!(received.len() + missed, 3);
+    });
+}
+
+/// 写入者连续写入 SIZE + 1 个值，超过读取者一整圈，读取者同时取两次最新值
+/// 读到的值必须递增，写入者结束之后最后写入的值一定能被读到
+fn writer_overtakes_reader<const SIZE: usize>() {
+    model(|| {
+        let (mut writer, mut reader) = make_value::<usize, SIZE>();
+        let producer = thread::spawn(move || {
+            for i in 1..=SIZE + 1 {
+                writer.push(i).unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            if let Ok(v) = reader.get_last() {
+                received.push(v);
+            }
+        }
+        producer.join().unwrap();
+        if let Ok(v) = reader.get_last() {
+            received.push(v);
+        }
+        assert!(received.windows(2).all(|w| w[0] < w[1]), "{received:?}");
+        assert_eq!(received.last(), Some(&(SIZE + 1)));
+        assert!(matches!(reader.get_last(), Err(Error::Disconnected)));
+    });
+}
+
+#[test]
+fn value_writer_overtakes_reader_2() {
+    writer_overtakes_reader::<2>();
+}
+
+#[test]
+fn value_writer_overtakes_reader_3() {
+    writer_overtakes_reader::<3>();
+}
+
+#[test]
+fn value_writer_overtakes_reader_4() {
+    writer_overtakes_reader::<4>();
+}
+
+/// 读取者通过引用原地访问最新值期间，写入者绕过它占用的槽位继续写入
+fn reader_holds_slot<const SIZE: usize>() {
+    model(|| {
+        let (mut writer, mut reader) = make_value_with::<usize, SIZE, Defaulted>();
+        writer.push(1).unwrap();
+        let producer = thread::spawn(move || {
+            for i in 2..=SIZE + 1 {
+                writer.push(i).unwrap();
+            }
+        });
+
+        if let Ok(v) = reader.get_last_ref() {
+            let seen = *v;
+            thread::yield_now();
+            assert_eq!(*v, seen);
+        }
+        producer.join().unwrap();
+    });
+}
+
+#[test]
+fn value_reader_holds_slot_2() {
+    reader_holds_slot::<2>();
+}
+
+#[test]
+fn value_reader_holds_slot_3() {
+    reader_holds_slot::<3>();
+}
+
+#[test]
+fn value_reader_holds_slot_4() {
+    reader_holds_slot::<4>();
+}
@@ -0,0 +1,98 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use lockfree::lockfree_mpmc::RingBuffer;
+use lockfree::error::{Error, TrySendError};
+
+#[test]
+fn mpmc_single_thread() {
+    let queue = RingBuffer::<u32, 4>::new();
+    assert!(queue.is_empty());
+    for i in 0..4 {
+        queue.push(i).unwrap();
+    }
+    assert!(queue.is_full());
+    assert_eq!(queue.len(), 4);
+    assert!(matches!(queue.push(4), Err(TrySendError::Full(4))));
+    for i in 0..4 {
+        assert_eq!(queue.pop().unwrap(), i);
+    }
+    assert!(matches!(queue.pop(), Err(Error::Empty)));
+}
+
+#[test]
+fn mpmc_many_threads() {
+    const THREADS: u64 = 4;
+    const COUNT: u64 = 20_000;
+    let queue = Arc::new(RingBuffer::<u64, 16>::new());
+    let sum = Arc::new(AtomicU64::new(0));
+    let received = Arc::new(AtomicU64::new(0));
+
+    let producers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for i in 1..=COUNT {
+                    let mut v = i;
+                    while let Err(TrySendError::Full(back)) = queue.push(v) {
+                        v = back;
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let consumers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let (queue, sum, received) = (queue.clone(), sum.clone(), received.clone());
+            thread::spawn(move || {
+                while received.load(Ordering::Relaxed) < THREADS * COUNT {
+                    match queue.pop() {
+                        Ok(v) => {
+                            sum.fetch_add(v, Ordering::Relaxed);
+                            received.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Err(_) => thread::yield_now(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for h in producers.into_iter().chain(consumers) {
+        h.join().unwrap();
+    }
+    assert_eq!(sum.load(Ordering::Relaxed), THREADS * COUNT * (COUNT + 1) / 2);
+    assert!(queue.is_empty());
+}
+
+/// 写满之后的写入必须失败，不能覆盖还没有取出的数据，每个值只析构一次
+#[test]
+fn mpmc_full_rejects_and_drops_once() {
+    use std::sync::atomic::AtomicUsize;
+
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let queue = RingBuffer::<Counted, 2>::new();
+    for _ in 0..2 {
+        assert!(queue.push(Counted(drops.clone())).is_ok());
+    }
+    assert!(matches!(queue.push(Counted(drops.clone())), Err(TrySendError::Full(_))));
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+    drop(queue.pop().unwrap());
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+    drop(queue);
+    assert_eq!(drops.load(Ordering::Relaxed), 3);
+}
@@ -0,0 +1,121 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::thread;
+
+use lockfree::lockfree_mpsc::ringbuffer;
+use lockfree::error::{Error, TrySendError};
+
+#[test]
+fn mpsc_single_thread() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    let mut tx2 = tx.clone();
+    assert_eq!(tx.capacity(), 4);
+    tx.push(1).unwrap();
+    tx2.push(2).unwrap();
+    tx.push(3).unwrap();
+    tx2.push(4).unwrap();
+    assert!(rx.is_full());
+    assert!(matches!(tx2.try_push(5), Err(TrySendError::Full(5))));
+    assert_eq!(rx.pop().unwrap(), 1);
+    assert_eq!(rx.pop().unwrap(), 2);
+    assert_eq!(rx.pop().unwrap(), 3);
+    assert_eq!(rx.pop().unwrap(), 4);
+    assert!(matches!(rx.pop(), Err(Error::Empty)));
+
+    drop(tx);
+    assert!(!rx.is_disconnected());
+    drop(tx2);
+    assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+}
+
+#[test]
+fn mpsc_many_producers() {
+    const PRODUCERS: u64 = 4;
+    const COUNT: u64 = 20_000;
+    let (tx, mut rx) = ringbuffer::<(u64, u64), 8>();
+
+    let handles: Vec<_> = (0..PRODUCERS)
+        .map(|p| {
+            let mut tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    tx.send((p, i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    // 每个写入者自己的数据保持顺序
+    let mut next = [0; PRODUCERS as usize];
+    loop {
+        match rx.recv() {
+            Ok((p, i)) => {
+                assert_eq!(next[p as usize], i);
+                next[p as usize] += 1;
+            }
+            Err(Error::Disconnected) => break,
+            Err(e) => panic!("unexpected {:?}", e),
+        }
+    }
+    assert!(next.iter().all(|&n| n == COUNT));
+    for h in handles {
+        h.join().unwrap();
+    }
+}
+
+#[test]
+fn mpsc_close() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    let mut tx2 = tx.clone();
+    tx.push(1).unwrap();
+    tx.close();
+    assert!(tx.is_closed());
+    assert!(!tx2.is_closed());
+    assert!(matches!(tx.try_push(2), Err(TrySendError::Closed(2))));
+    assert!(matches!(tx.reserve(), Err(Error::Closed)));
+    // 关闭之后的克隆也是关闭的
+    assert!(tx.clone().is_closed());
+
+    tx2.push(3).unwrap();
+    tx2.close();
+    assert_eq!(rx.pop().unwrap(), 1);
+    assert_eq!(rx.pop().unwrap(), 3);
+    assert!(matches!(rx.pop(), Err(Error::Disconnected)));
+}
+
+#[test]
+fn mpsc_reserve_and_abort() {
+    let (mut tx, mut rx) = ringbuffer::<String, 4>();
+    let mut tx2 = tx.clone();
+    let mut guard = tx.reserve().unwrap();
+    guard.write("first".to_string());
+    // 先占用的槽位没有提交时，后写入的数据对读取者不可见
+    tx2.push("second".to_string()).unwrap();
+    assert!(matches!(rx.pop(), Err(Error::Empty)));
+    guard.commit();
+    assert_eq!(rx.pop().unwrap(), "first");
+    assert_eq!(rx.pop().unwrap(), "second");
+
+    // 放弃的槽位被读取者跳过
+    let mut guard = tx.reserve().unwrap();
+    guard.write("dropped".to_string());
+    tx2.push("third".to_string()).unwrap();
+    drop(guard);
+    assert_eq!(rx.pop().unwrap(), "third");
+    assert!(rx.is_empty());
+}
+
+#[test]
+fn mpsc_peek_and_slices() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    assert_eq!(tx.push_slice(&[1, 2, 3, 4, 5]), 4);
+    assert_eq!(*rx.peek().unwrap(), 1);
+    *rx.peek_mut().unwrap() = 10;
+    assert_eq!(rx.pop_guard().unwrap().commit(), 10);
+
+    let mut out = [0; 4];
+    assert_eq!(rx.pop_into(&mut out), 3);
+    assert_eq!(out, [2, 3, 4, 0]);
+}
@@ -0,0 +1,626 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::thread;
+
+use lockfree::error::{Error, TrySendError};
+use lockfree::queue::{ringbuffer, ringbuffer_overwrite, ringbuffer_overwrite_with, ringbuffer_with};
+use lockfree::storage::Defaulted;
+
+#[test]
+fn sender_receiver_state() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    assert!(tx.is_empty() && rx.is_empty());
+    assert_eq!(tx.capacity(), 4);
+    assert_eq!(tx.remaining(), 4);
+
+    tx.try_push(1).unwrap();
+    tx.push(2).unwrap();
+    tx.push(3).unwrap();
+    tx.push(4).unwrap();
+    assert!(tx.is_full() && rx.is_full());
+    assert_eq!(rx.len(), 4);
+    assert_eq!(tx.remaining(), 0);
+
+    match tx.try_push(5) {
+        Err(TrySendError::Full(v)) => assert_eq!(v, 5),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert_eq!(rx.try_pop().unwrap(), 1);
+    assert_eq!(rx.pop().unwrap(), 2);
+    assert_eq!(rx.len(), 2);
+    assert_eq!(rx.pop().unwrap(), 3);
+    assert_eq!(rx.pop().unwrap(), 4);
+    assert!(matches!(rx.pop(), Err(Error::Empty)));
+}
+
+#[test]
+fn spsc_across_threads() {
+    const COUNT: u64 = 100_000;
+    let (mut tx, mut rx) = ringbuffer::<u64, 64>();
+
+    let producer = thread::spawn(move || {
+        let mut i = 0;
+        while i < COUNT {
+            match tx.try_push(i) {
+                Ok(()) => i += 1,
+                Err(TrySendError::Full(v)) => {
+                    assert_eq!(v, i);
+                    thread::yield_now();
+                }
+                Err(e) => panic!("unexpected {:?}", e),
+            }
+        }
+    });
+
+    let consumer = thread::spawn(move || {
+        let mut expected = 0;
+        while expected < COUNT {
+            match rx.try_pop() {
+                Ok(v) => {
+                    assert_eq!(v, expected);
+                    expected += 1;
+                }
+                Err(Error::Empty) => thread::yield_now(),
+                Err(e) => panic!("unexpected {:?}", e),
+            }
+        }
+        assert!(rx.is_empty());
+    });
+
+    producer.join().unwrap();
+    consumer.join().unwrap();
+}
+
+#[test]
+fn default_spsc_across_threads() {
+    const COUNT: u64 = 100_000;
+    let (mut tx, mut rx) = ringbuffer_with::<u64, 16, Defaulted>();
+
+    let producer = thread::spawn(move || {
+        let mut i = 0;
+        while i < COUNT {
+            match tx.try_push(i) {
+                Ok(()) => i += 1,
+                Err(TrySendError::Full(_)) => thread::yield_now(),
+                Err(e) => panic!("unexpected {:?}", e),
+            }
+        }
+    });
+
+    let mut expected = 0;
+    while expected < COUNT {
+        match rx.try_pop() {
+            Ok(v) => {
+                assert_eq!(v, expected);
+                expected += 1;
+            }
+            Err(Error::Empty) => thread::yield_now(),
+            Err(e) => panic!("unexpected {:?}", e),
+        }
+    }
+    producer.join().unwrap();
+}
+
+#[test]
+fn blocking_send_recv() {
+    const COUNT: u64 = 100_000;
+    let (mut tx, mut rx) = ringbuffer::<u64, 4>();
+
+    let producer = thread::spawn(move || {
+        for i in 0..COUNT {
+            tx.send(i).unwrap();
+        }
+    });
+
+    for i in 0..COUNT {
+        assert_eq!(rx.recv().unwrap(), i);
+    }
+    producer.join().unwrap();
+}
+
+#[test]
+fn recv_wakes_parked_receiver() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 1>();
+
+    let consumer = thread::spawn(move || (rx.recv().unwrap(), rx.recv().unwrap()));
+
+    thread::sleep(std::time::Duration::from_millis(50));
+    tx.send(1).unwrap();
+    // 队列只能容纳一个元素，第二次写入会等待读取者取走第一个
+    tx.send(2).unwrap();
+    assert_eq!(consumer.join().unwrap(), (1, 2));
+}
+
+#[test]
+fn send_recv_timeout() {
+    use lockfree::error::SendTimeoutError;
+    use std::time::{Duration, Instant};
+
+    let (mut tx, mut rx) = ringbuffer::<u32, 1>();
+    assert!(matches!(rx.recv_timeout(Duration::from_millis(10)), Err(Error::Timeout)));
+    assert!(matches!(rx.recv_deadline(Instant::now()), Err(Error::Timeout)));
+
+    tx.send_timeout(1, Duration::from_millis(10)).unwrap();
+    match tx.send_timeout(2, Duration::from_millis(10)) {
+        Err(SendTimeoutError::Timeout(v)) => assert_eq!(v, 2),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let producer = thread::spawn(move || tx.send_deadline(2, Instant::now() + Duration::from_secs(5)));
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 1);
+    producer.join().unwrap().unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 2);
+}
+
+#[test]
+fn disconnect_after_drain() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    tx.push(1).unwrap();
+    tx.push(2).unwrap();
+    drop(tx);
+    assert!(rx.is_disconnected());
+    assert_eq!(rx.pop().unwrap(), 1);
+    assert_eq!(rx.recv().unwrap(), 2);
+    assert!(matches!(rx.pop(), Err(Error::Disconnected)));
+    assert!(matches!(rx.recv(), Err(Error::Disconnected)));
+}
+
+#[test]
+fn disconnect_wakes_blocked_peer() {
+    use lockfree::error::SendError;
+
+    let (tx, mut rx) = ringbuffer::<u32, 2>();
+    let consumer = thread::spawn(move || rx.recv());
+    thread::sleep(std::time::Duration::from_millis(20));
+    drop(tx);
+    assert!(matches!(consumer.join().unwrap(), Err(Error::Disconnected)));
+
+    let (mut tx, rx) = ringbuffer::<u32, 1>();
+    tx.send(1).unwrap();
+    let producer = thread::spawn(move || tx.send(2));
+    thread::sleep(std::time::Duration::from_millis(20));
+    drop(rx);
+    assert_eq!(producer.join().unwrap(), Err(SendError::Disconnected(2)));
+}
+
+#[test]
+fn overwrite_oldest() {
+    let (mut tx, mut rx) = ringbuffer_overwrite::<u32, 4>();
+    assert!(tx.is_overwrite());
+    for i in 0..6 {
+        tx.push(i).unwrap();
+    }
+    assert!(rx.is_full());
+    assert_eq!(rx.pop().unwrap(), 2);
+    assert_eq!(rx.missed(), 2);
+    assert_eq!(rx.pop().unwrap(), 3);
+    assert_eq!(rx.missed(), 0);
+    tx.send(6).unwrap();
+    assert_eq!(rx.pop().unwrap(), 4);
+    assert_eq!(rx.pop().unwrap(), 5);
+    assert_eq!(rx.pop().unwrap(), 6);
+    assert!(matches!(rx.pop(), Err(Error::Empty)));
+}
+
+#[test]
+fn overwrite_concurrent() {
+    const COUNT: u64 = 200_000;
+    let (mut tx, mut rx) = ringbuffer_overwrite::<u64, 8>();
+
+    let producer = thread::spawn(move || {
+        for i in 0..COUNT {
+            tx.push(i).unwrap();
+        }
+    });
+
+    let mut received = 0;
+    let mut missed = 0;
+    let mut last = None;
+    loop {
+        match rx.pop() {
+            Ok(v) => {
+                // 数据保持写入顺序，被覆盖的只会是更早的数据
+                assert!(last.is_none_or(|last| v > last));
+                last = Some(v);
+                received += 1;
+                missed += rx.missed() as u64;
+            }
+            Err(Error::Empty) => thread::yield_now(),
+            Err(Error::Disconnected) => break,
+            Err(e) => panic!("unexpected {:?}", e),
+        }
+    }
+    producer.join().unwrap();
+    assert_eq!(last, Some(COUNT - 1));
+    assert_eq!(received + missed, COUNT);
+}
+
+#[test]
+fn reserve_commit_abort() {
+    let (mut tx, mut rx) = ringbuffer::<Vec<u8>, 4>();
+    let mut slot = tx.reserve().unwrap();
+    slot.write(vec![1, 2]).push(3);
+    slot.commit();
+    // 没有提交的预留会被放弃
+    tx.reserve().unwrap().write(vec![4]);
+    assert!(matches!(rx.pop(), Ok(v) if v == [1, 2, 3]));
+    assert!(matches!(rx.pop(), Err(Error::Empty)));
+
+    let (mut tx, mut rx) = ringbuffer_with::<[u64; 32], 1, Defaulted>();
+    let mut slot = tx.reserve().unwrap();
+    slot[0] = 7;
+    slot[31] = 9;
+    slot.commit();
+    assert!(tx.reserve().is_err());
+    let v = rx.pop().unwrap();
+    assert_eq!((v[0], v[31]), (7, 9));
+    {
+        let mut slot = tx.reserve().unwrap();
+        slot[0] = 1;
+    }
+    // 放弃的预留不会留下写了一半的数据
+    assert_eq!(tx.reserve().unwrap()[0], 0);
+    drop(rx);
+    assert!(tx.reserve().is_err());
+}
+
+#[test]
+fn peek_and_pop_guard() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    assert!(rx.peek().is_none());
+    assert!(matches!(rx.pop_guard(), Err(Error::Empty)));
+    tx.push(1).unwrap();
+    tx.push(2).unwrap();
+    assert_eq!(rx.peek().as_deref(), Some(&1));
+    *rx.peek_mut().unwrap() = 10;
+
+    // 处理失败，数据留在队列中
+    let item = rx.pop_guard().unwrap();
+    assert_eq!(*item, 10);
+    drop(item);
+    assert_eq!(rx.len(), 2);
+    assert_eq!(rx.pop_guard().unwrap().commit(), 10);
+    assert_eq!(rx.pop().unwrap(), 2);
+
+    drop(tx);
+    assert!(matches!(rx.pop_guard(), Err(Error::Disconnected)));
+
+    let (mut tx, mut rx) = ringbuffer_with::<String, 4, Defaulted>();
+    tx.push("a".to_string()).unwrap();
+    rx.peek_mut().unwrap().push('b');
+    assert_eq!(rx.pop_guard().unwrap().as_str(), "ab");
+    assert_eq!(rx.pop_guard().unwrap().commit(), "ab");
+}
+
+#[test]
+fn peek_releases_pin() {
+    let (mut tx, mut rx) = ringbuffer_overwrite::<u32, 2>();
+    tx.push(1).unwrap();
+    tx.push(2).unwrap();
+    let item = rx.peek().unwrap();
+    assert_eq!(*item, 1);
+    // 查看期间最早的数据不会被丢弃，新写入的数据被丢弃
+    tx.push(3).unwrap();
+    assert_eq!(*item, 1);
+    drop(item);
+    // 查看结束之后恢复丢弃最早的数据
+    tx.push(4).unwrap();
+    assert_eq!(rx.peek().as_deref(), Some(&2));
+    tx.push(5).unwrap();
+    assert_eq!(rx.pop().unwrap(), 4);
+    assert_eq!(rx.missed(), 3);
+    assert_eq!(rx.pop().unwrap(), 5);
+    assert_eq!(rx.missed(), 0);
+}
+
+#[test]
+fn pinned_item_survives_overwrite() {
+    let (mut tx, mut rx) = ringbuffer_overwrite::<u32, 4>();
+    for i in 0..4 {
+        tx.push(i).unwrap();
+    }
+    let item = rx.pop_guard().unwrap();
+    // 最早的数据被占用，队列已满时丢弃新写入的数据
+    tx.push(4).unwrap();
+    assert!(tx.reserve().is_err());
+    assert_eq!(*item, 0);
+    drop(item);
+    tx.push(5).unwrap();
+    assert_eq!(rx.pop().unwrap(), 1);
+    assert_eq!(rx.missed(), 2);
+    assert_eq!(rx.pop().unwrap(), 2);
+    assert_eq!(rx.pop().unwrap(), 3);
+    assert_eq!(rx.pop().unwrap(), 5);
+}
+
+#[test]
+fn batch_push_pop_wraps() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 8>();
+    let mut out = [0; 8];
+    assert_eq!(tx.push_slice(&[1, 2, 3, 4, 5]), 5);
+    assert_eq!(rx.pop_into(&mut out[..3]), 3);
+    assert_eq!(out[..3], [1, 2, 3]);
+    // 跨过回绕点，只写入剩余的空位
+    assert_eq!(tx.push_slice(&[6, 7, 8, 9, 10, 11, 12]), 6);
+    assert_eq!(rx.pop_into(&mut out), 8);
+    assert_eq!(out, [4, 5, 6, 7, 8, 9, 10, 11]);
+    assert_eq!(rx.pop_into(&mut out), 0);
+
+    let (mut tx, mut rx) = ringbuffer_with::<f32, 8, Defaulted>();
+    assert_eq!(tx.push_slice(&[0.0; 6]), 6);
+    assert_eq!(rx.pop_into(&mut [0.0; 4]), 4);
+    assert_eq!(tx.push_slice(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]), 6);
+    let mut out = [0.0; 8];
+    assert_eq!(rx.pop_into(&mut out), 8);
+    assert_eq!(out, [0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+}
+
+#[test]
+fn batch_across_threads() {
+    const COUNT: u32 = 100_000;
+    let (mut tx, mut rx) = ringbuffer_with::<u32, 64, Defaulted>();
+
+    let producer = thread::spawn(move || {
+        let samples: Vec<u32> = (0..COUNT).collect();
+        let mut sent = 0;
+        while sent < samples.len() {
+            let end = (sent + 17).min(samples.len());
+            match tx.push_slice(&samples[sent..end]) {
+                0 => thread::yield_now(),
+                n => sent += n,
+            }
+        }
+    });
+
+    let mut expected = 0;
+    let mut out = [0; 23];
+    while expected < COUNT {
+        let n = rx.pop_into(&mut out);
+        for v in &out[..n] {
+            assert_eq!(*v, expected);
+            expected += 1;
+        }
+        if n == 0 {
+            thread::yield_now();
+        }
+    }
+    producer.join().unwrap();
+}
+
+#[test]
+fn read_write_chunks() {
+    let (mut tx, mut rx) = ringbuffer_with::<u8, 8, Defaulted>();
+    tx.push_slice(b"abcde");
+    assert_eq!(rx.pop_into(&mut [0; 4]), 4);
+
+    // 空闲区域跨过回绕点
+    let mut chunk = tx.write_chunk(6).unwrap();
+    assert_eq!(chunk.len(), 6);
+    let (first, second) = chunk.as_mut_slices();
+    assert_eq!((first.len(), second.len()), (3, 3));
+    first.copy_from_slice(b"fgh");
+    second.copy_from_slice(b"ijk");
+    chunk.commit(5);
+    assert_eq!(tx.len(), 6);
+
+    let chunk = rx.read_chunk(8).unwrap();
+    let (first, second) = chunk.as_slices();
+    assert_eq!((first, second), (&b"efgh"[..], &b"ij"[..]));
+    chunk.commit(3);
+    // 没有提交的数据留在队列中
+    assert_eq!(rx.read_chunk(8).unwrap().len(), 3);
+    assert_eq!(rx.read_chunk(8).unwrap().as_slices(), (&b"h"[..], &b"ij"[..]));
+    rx.read_chunk(8).unwrap().commit_all();
+    assert!(matches!(rx.read_chunk(1), Err(Error::Empty)));
+
+    // 放弃写入的区域不会被读取者看到
+    drop(tx.write_chunk(2).unwrap());
+    assert!(matches!(rx.read_chunk(1), Err(Error::Empty)));
+    let mut chunk = tx.write_chunk(8).unwrap();
+    let (first, second) = chunk.as_mut_slices();
+    first.iter_mut().chain(second).for_each(|v| *v = b'z');
+    chunk.commit_all();
+    assert!(matches!(tx.write_chunk(1), Err(Error::Full)));
+    drop(tx);
+    let chunk = rx.read_chunk(8).unwrap();
+    let (first, second) = chunk.as_slices();
+    assert_eq!(chunk.len(), 8);
+    assert!(first.iter().chain(second).all(|v| *v == b'z'));
+    // 没有提交就释放，数据留在队列中
+    drop(chunk);
+    rx.read_chunk(8).unwrap().commit_all();
+    assert!(matches!(rx.read_chunk(1), Err(Error::Disconnected)));
+}
+
+#[test]
+fn drops_only_live_items() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // 没有实现 Default，析构时计数
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let item = || Counted(drops.clone());
+
+    let (mut tx, mut rx) = ringbuffer::<Counted, 4>();
+    for _ in 0..4 {
+        assert!(tx.push(item()).is_ok());
+    }
+    drop(rx.pop().unwrap());
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+    // 放弃的预留析构写入的数据，重复写入析构之前的数据
+    let mut slot = tx.reserve().unwrap();
+    slot.write(item());
+    slot.write(item());
+    drop(slot);
+    assert_eq!(drops.load(Ordering::Relaxed), 3);
+    drop(rx.pop_guard().unwrap());
+    drop((tx, rx));
+    assert_eq!(drops.load(Ordering::Relaxed), 6);
+
+    drops.store(0, Ordering::Relaxed);
+    let (mut tx, mut rx) = ringbuffer_overwrite::<Counted, 2>();
+    for _ in 0..5 {
+        assert!(tx.push(item()).is_ok());
+    }
+    assert_eq!(drops.load(Ordering::Relaxed), 3);
+    drop(rx.pop().unwrap());
+    drop((tx, rx));
+    assert_eq!(drops.load(Ordering::Relaxed), 5);
+
+    drops.store(0, Ordering::Relaxed);
+    let (mut tx, mut rx) = lockfree::lockfree_heap::ringbuffer::<Counted>(4).unwrap();
+    let queue = lockfree::lockfree_mpmc::RingBuffer::<Counted, 4>::new();
+    for _ in 0..3 {
+        assert!(tx.push(item()).is_ok());
+        assert!(queue.push(item()).is_ok());
+    }
+    drop(rx.pop().unwrap());
+    drop(queue.pop().unwrap());
+    drop((tx, rx, queue));
+    assert_eq!(drops.load(Ordering::Relaxed), 6);
+}
+
+#[test]
+fn storage_policies() {
+    use lockfree::storage::Optional;
+
+    let (mut tx, mut rx) = ringbuffer_overwrite_with::<String, 2, Optional>();
+    for s in ["a", "b", "c"] {
+        tx.push(s.to_string()).unwrap();
+    }
+    assert_eq!(rx.pop().unwrap(), "b");
+    assert_eq!(rx.missed(), 1);
+    let mut slot = tx.reserve().unwrap();
+    assert!(slot.get_mut().is_none());
+    slot.write("d".to_string());
+    slot.commit();
+    assert_eq!(rx.peek().as_deref().map(String::as_str), Some("c"));
+}
+
+#[test]
+fn overwrite_read_chunk() {
+    // 覆盖模式下被 read_chunk 占用的数据不会被丢弃
+    let (mut tx, mut rx) = ringbuffer_overwrite_with::<u8, 4, Defaulted>();
+    tx.push_slice(b"abcdef");
+    assert_eq!(rx.missed(), 0);
+    let chunk = rx.read_chunk(2).unwrap();
+    assert_eq!(chunk.as_slices(), (&b"cd"[..], &b""[..]));
+    tx.push(b'g').unwrap();
+    assert_eq!(chunk.as_slices(), (&b"cd"[..], &b""[..]));
+    chunk.commit(1);
+    assert_eq!(rx.missed(), 3);
+    assert_eq!(rx.pop().unwrap(), b'd');
+    assert_eq!(rx.pop().unwrap(), b'e');
+    assert_eq!(rx.pop().unwrap(), b'f');
+    assert!(matches!(rx.pop(), Err(Error::Empty)));
+}
+
+#[test]
+fn read_chunk_races_overwrite() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    // 写入者可能在 read_chunk 检查长度之后丢弃仅剩的数据，这时返回 Empty 而不是 panic
+    let (mut tx, mut rx) = ringbuffer_overwrite_with::<u8, 1, Defaulted>();
+    let stop = Arc::new(AtomicBool::new(false));
+    let producer = {
+        let stop = stop.clone();
+        thread::spawn(move || {
+            let mut i = 0u8;
+            while !stop.load(Ordering::Relaxed) {
+                tx.push(i).unwrap();
+                i = i.wrapping_add(1);
+            }
+        })
+    };
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(200);
+    while std::time::Instant::now() < deadline {
+        match rx.read_chunk(1) {
+            Ok(chunk) => chunk.commit_all(),
+            Err(Error::Empty) => {}
+            Err(err) => panic!("unexpected {err:?}"),
+        }
+    }
+    stop.store(true, Ordering::Relaxed);
+    producer.join().unwrap();
+}
+
+#[test]
+fn read_chunk_released_on_drop() {
+    // 覆盖模式下释放或者提交 0 个元素都会放弃占用，写入者恢复丢弃最早的数据
+    let (mut tx, mut rx) = ringbuffer_overwrite_with::<u8, 2, Defaulted>();
+    tx.push_slice(b"ab");
+    let chunk = rx.read_chunk(2).unwrap();
+    tx.push(b'c').unwrap();
+    assert_eq!(chunk.as_slices(), (&b"ab"[..], &b""[..]));
+    drop(chunk);
+    tx.push(b'd').unwrap();
+    assert_eq!(rx.read_chunk(2).unwrap().as_slices(), (&b"b"[..], &b"d"[..]));
+    rx.read_chunk(1).unwrap().commit(0);
+    tx.push(b'e').unwrap();
+    assert_eq!(rx.pop().unwrap(), b'd');
+    assert_eq!(rx.missed(), 3);
+    assert_eq!(rx.pop().unwrap(), b'e');
+    assert!(matches!(rx.pop(), Err(Error::Empty)));
+}
+
+#[test]
+fn uninit_chunks() {
+    let (mut tx, mut rx) = ringbuffer::<String, 4>();
+    tx.push("a".to_string()).unwrap();
+    assert_eq!(rx.pop().unwrap(), "a");
+
+    let mut chunk = tx.write_chunk(4).unwrap();
+    let (first, second) = chunk.as_mut_slices();
+    assert_eq!((first.len(), second.len()), (3, 1));
+    for (slot, s) in first.iter_mut().chain(second).zip(["b", "c", "d"]) {
+        slot.write(s.to_string());
+    }
+    unsafe { chunk.commit(3) };
+    assert_eq!(tx.len(), 3);
+
+    let chunk = rx.read_chunk(4).unwrap();
+    assert_eq!(chunk.as_slices(), (&["b".to_string(), "c".to_string(), "d".to_string()][..], &[][..]));
+    chunk.commit(2);
+    let mut chunk = rx.read_chunk(4).unwrap();
+    chunk.as_mut_slices().0[0].push('!');
+    drop(chunk);
+    assert_eq!(rx.pop().unwrap(), "d!");
+    assert!(matches!(rx.read_chunk(1), Err(Error::Empty)));
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_paths() {
+    use lockfree::queue::{RingBufferReceiver, RingBufferSender};
+    use lockfree::value::{ValueReader, ValueWriter};
+
+    let (mut tx, mut rx): (RingBufferSender<u32, 4>, RingBufferReceiver<u32, 4>) = lockfree::lockfree_queue::ringbuffer();
+    tx.push(1).unwrap();
+    assert_eq!(rx.pop().unwrap(), 1);
+
+    let (mut tx, mut rx): (RingBufferSender<u32, 4, Defaulted>, RingBufferReceiver<u32, 4, Defaulted>) =
+        lockfree::default::queue::ringbuffer();
+    tx.reserve().unwrap().commit();
+    assert!(matches!(rx.pop(), Ok(0)));
+    assert!(matches!(rx.pop(), Err(lockfree::default::error::Error::Empty)));
+
+    let (mut writer, mut reader): (ValueWriter<u32, 4>, ValueReader<u32, 4>) = lockfree::lockfree_value::make_value();
+    assert_eq!(writer.push(1).unwrap(), None);
+    assert_eq!(reader.get_last().unwrap(), 1);
+
+    let (mut writer, mut reader): (ValueWriter<u32, 4, Defaulted>, ValueReader<u32, 4, Defaulted>) =
+        lockfree::default::value::make_value();
+    assert_eq!(writer.push(2).unwrap(), 0);
+    assert_eq!(*reader.get_last_ref().unwrap(), 2);
+}
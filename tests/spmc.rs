@@ -0,0 +1,120 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::collections::HashSet;
+use std::thread;
+
+use lockfree::error::{Error, TrySendError};
+use lockfree::lockfree_spmc::ringbuffer;
+
+#[test]
+fn spmc_single_thread() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    let mut rx2 = rx.clone();
+    tx.push(1).unwrap();
+    tx.push(2).unwrap();
+    tx.push(3).unwrap();
+    tx.push(4).unwrap();
+    assert!(matches!(tx.try_push(5), Err(TrySendError::Full(5))));
+    assert_eq!(rx.pop().unwrap(), 1);
+    assert_eq!(rx2.pop().unwrap(), 2);
+    assert_eq!(rx.pop().unwrap(), 3);
+    assert_eq!(rx2.pop().unwrap(), 4);
+    assert!(matches!(rx2.pop(), Err(Error::Empty)));
+
+    drop(rx);
+    assert!(!tx.is_disconnected());
+    drop(rx2);
+    assert!(matches!(tx.try_push(6), Err(TrySendError::Disconnected(6))));
+}
+
+#[test]
+fn spmc_each_item_delivered_once() {
+    const WORKERS: usize = 4;
+    const COUNT: u64 = 50_000;
+    let (mut tx, rx) = ringbuffer::<u64, 8>();
+
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut taken = Vec::new();
+                loop {
+                    match rx.recv() {
+                        Ok(v) => taken.push(v),
+                        Err(Error::Disconnected) => return taken,
+                        Err(e) => panic!("unexpected {:?}", e),
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(rx);
+
+    for i in 0..COUNT {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+
+    let mut seen = HashSet::new();
+    for w in workers {
+        let taken = w.join().unwrap();
+        // 每个读取者拿到的数据保持写入顺序
+        assert!(taken.windows(2).all(|w| w[0] < w[1]));
+        for v in taken {
+            assert!(seen.insert(v));
+        }
+    }
+    assert_eq!(seen.len() as u64, COUNT);
+}
+
+#[test]
+fn spmc_close() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    let mut rx2 = rx.clone();
+    tx.push(1).unwrap();
+    tx.close();
+    assert!(tx.is_closed());
+    assert!(matches!(tx.try_push(2), Err(TrySendError::Closed(2))));
+    assert!(rx2.is_disconnected());
+    assert_eq!(rx2.pop().unwrap(), 1);
+    assert!(matches!(rx.pop(), Err(Error::Disconnected)));
+}
+
+#[test]
+fn spmc_guards() {
+    let (mut tx, mut rx) = ringbuffer::<String, 4>();
+    let mut rx2 = rx.clone();
+    let mut guard = tx.reserve().unwrap();
+    guard.write("a".to_string());
+    guard.commit();
+    // 放弃写入之后槽位可以再次使用
+    tx.reserve().unwrap().write("dropped".to_string());
+    tx.push("b".to_string()).unwrap();
+
+    // 被查看的数据不会被其他读取者取走
+    let peek = rx.peek().unwrap();
+    assert_eq!(*peek, "a");
+    assert!(matches!(rx2.pop(), Err(Error::Empty)));
+    assert_eq!(rx2.len(), 2);
+    drop(peek);
+
+    let mut guard = rx2.pop_guard().unwrap();
+    guard.push('!');
+    drop(guard);
+    assert_eq!(rx.pop().unwrap(), "a!");
+    assert_eq!(rx2.pop_guard().unwrap().commit(), "b");
+    assert!(rx.is_empty());
+}
+
+#[test]
+fn spmc_slices() {
+    let (mut tx, mut rx) = ringbuffer::<u32, 4>();
+    let mut rx2 = rx.clone();
+    assert_eq!(tx.push_slice(&[1, 2, 3, 4, 5]), 4);
+    let mut out = [0; 2];
+    assert_eq!(rx.pop_into(&mut out), 2);
+    assert_eq!(out, [1, 2]);
+    assert_eq!(rx2.pop_into(&mut out), 2);
+    assert_eq!(out, [3, 4]);
+}
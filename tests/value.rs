@@ -0,0 +1,142 @@
+// 以 `--cfg loom` 编译时原子类型只能在 loom::model 中使用，这些测试只在默认实现下运行
+#![cfg(not(loom))]
+
+use std::thread;
+
+use lockfree::value::make_value;
+
+#[test]
+fn latest_value_single_thread() {
+    let (mut writer, mut reader) = make_value::<String, 4>();
+    assert!(reader.unchanged());
+    writer.push("a".to_string()).unwrap();
+    writer.push("b".to_string()).unwrap();
+    assert!(reader.changed());
+    assert_eq!(reader.get_last().unwrap(), "b");
+    assert!(reader.unchanged());
+}
+
+#[test]
+fn non_power_of_two_size() {
+    // SIZE 不要求是2的幂，下一个槽位在最后一个之后回绕到0
+    let (mut writer, mut reader) = make_value::<u32, 3>();
+    assert_eq!(writer.size(), 3);
+    for i in 1..=7 {
+        writer.push(i).unwrap();
+        assert!(writer.next_idx() < 3);
+        if i % 2 == 0 {
+            assert_eq!(reader.get_last().unwrap(), i);
+        }
+    }
+    assert_eq!(reader.get_last().unwrap(), 7);
+}
+
+#[test]
+fn latest_value_across_threads() {
+    const COUNT: u64 = 100_000;
+    let (mut writer, mut reader) = make_value::<u64, 4>();
+
+    let producer = thread::spawn(move || {
+        for i in 1..=COUNT {
+            writer.push(i).unwrap();
+        }
+    });
+
+    let mut last = 0;
+    while last < COUNT {
+        if let Ok(v) = reader.get_last() {
+            assert!(v > last);
+            last = v;
+        }
+    }
+    producer.join().unwrap();
+}
+
+#[test]
+fn default_latest_value() {
+    use lockfree::error::Error;
+    use lockfree::storage::Defaulted;
+    use lockfree::value::make_value_with;
+
+    let (mut writer, mut reader) = make_value_with::<u64, 4, Defaulted>();
+    assert!(matches!(reader.get_last_ref(), Err(Error::Empty)));
+    writer.push(7).unwrap();
+    assert_eq!(*reader.get_last_ref().unwrap(), 7);
+    assert!(matches!(reader.get_last(), Err(Error::Empty)));
+    writer.push(8).unwrap();
+    *reader.get_last_mut().unwrap() += 1;
+    writer.push(10).unwrap();
+    assert_eq!(reader.get_last().unwrap(), 10);
+}
+
+#[test]
+fn wait_changed_timeout() {
+    use lockfree::error::Error;
+    use std::time::Duration;
+
+    let (mut writer, mut reader) = make_value::<u32, 4>();
+    assert!(matches!(reader.wait_changed_timeout(Duration::from_millis(10)), Err(Error::Timeout)));
+
+    let producer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        writer.push(5).unwrap();
+    });
+    reader.wait_changed_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(reader.get_last().unwrap(), 5);
+    producer.join().unwrap();
+}
+
+#[test]
+fn writer_disconnect() {
+    use lockfree::error::Error;
+
+    let (mut writer, mut reader) = make_value::<u32, 4>();
+    writer.push(1).unwrap();
+    drop(writer);
+    assert!(reader.is_disconnected());
+    assert_eq!(reader.get_last().unwrap(), 1);
+    assert!(matches!(reader.get_last(), Err(Error::Disconnected)));
+    assert!(matches!(reader.wait_changed(), Err(Error::Disconnected)));
+}
+
+#[test]
+fn reader_disconnect() {
+    use lockfree::error::SendError;
+
+    let (mut writer, reader) = make_value::<String, 4>();
+    assert_eq!(writer.push("a".to_string()).unwrap(), None);
+    drop(reader);
+    assert!(writer.is_disconnected());
+    let SendError::Disconnected(value) = writer.push("b".to_string()).unwrap_err() else {
+        panic!("expected Disconnected");
+    };
+    assert_eq!(value, "b");
+}
+
+#[test]
+fn get_last_same_shape_for_every_storage() {
+    use lockfree::error::Error;
+    use lockfree::storage::{Defaulted, Optional};
+    use lockfree::value::LockFreeValue;
+
+    let mut value = LockFreeValue::<u32, 4, Optional>::new();
+    assert!(matches!(value.get_last(), Err(Error::Empty)));
+    value.push(1);
+    assert_eq!(value.get_last().unwrap(), 1);
+    assert!(matches!(value.get_last(), Err(Error::Empty)));
+
+    let mut value = LockFreeValue::<u32, 4, Defaulted>::new();
+    assert!(matches!(value.get_last(), Err(Error::Empty)));
+    value.push(2);
+    assert_eq!(value.get_last().unwrap(), 2);
+    assert!(matches!(value.get_last(), Err(Error::Empty)));
+
+    #[allow(deprecated)]
+    {
+        let mut value = lockfree::lockfree_value::LockFreeValue::<u32, 4>::new();
+        assert_eq!(value.get_last(), None);
+        value.push(3);
+        assert_eq!(value.get_last(), Some(3));
+        assert_eq!(value.get_last(), None);
+    }
+}